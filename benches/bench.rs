@@ -1,5 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use twitter_snowflake::Snowflake;
+use std::time::Duration;
+use twitter_snowflake::{CoarseClock, ConstSnowflake, Snowflake};
 
 fn bench_new(c: &mut Criterion) {
     c.bench_function("bench_new", |b| {
@@ -39,5 +40,50 @@ fn bench_generate(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_new, bench_builder, bench_generate);
+// A single worker ID bit leaves 21 bits of sequence, so the clock rather than
+// the sequence limits the rate
+fn bench_generate_precise_clock(c: &mut Criterion) {
+    let mut snowflake = Snowflake::builder().with_worker_id_bits(1).build().unwrap();
+    c.bench_function("bench_generate_precise_clock", |b| {
+        b.iter(|| {
+            let _ = black_box(snowflake.generate().unwrap());
+        });
+    });
+}
+
+fn bench_generate_coarse_clock(c: &mut Criterion) {
+    let clock = CoarseClock::start(Duration::from_millis(1)).unwrap();
+    let mut snowflake = Snowflake::builder()
+        .with_worker_id_bits(1)
+        .with_clock(clock)
+        .build()
+        .unwrap();
+    c.bench_function("bench_generate_coarse_clock", |b| {
+        b.iter(|| {
+            let _ = black_box(snowflake.generate().unwrap());
+        });
+    });
+}
+
+// The same layout as bench_generate_precise_clock, with the bits fixed at
+// compile time
+fn bench_generate_const(c: &mut Criterion) {
+    let worker_id = 1;
+    let mut snowflake: ConstSnowflake<1, 21> = Snowflake::builder().with_worker_id(worker_id).build_const().unwrap();
+    c.bench_function("bench_generate_const", |b| {
+        b.iter(|| {
+            let _ = black_box(snowflake.generate().unwrap());
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_new,
+    bench_builder,
+    bench_generate,
+    bench_generate_const,
+    bench_generate_precise_clock,
+    bench_generate_coarse_clock
+);
 criterion_main!(benches);
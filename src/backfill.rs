@@ -0,0 +1,69 @@
+use {
+    crate::{Layout, SnowflakeError},
+    std::{collections::HashMap, time::SystemTime},
+};
+
+/// A generator of Snowflake IDs for caller-supplied past timestamps.
+///
+/// Unlike [`Snowflake`](crate::Snowflake), which always stamps IDs with the
+/// current time, a backfill generator stamps each ID with the given timestamp,
+/// so migrated rows keep their original creation time. Timestamps may arrive
+/// in any order; the next sequence is tracked per time period, which takes
+/// memory proportional to the number of distinct periods seen.
+///
+/// Live generators may already have used any ID of a past time period, so a
+/// backfill generator should be given a worker ID reserved for backfilling.
+#[derive(Debug)]
+pub struct Backfill {
+    layout: Layout,
+    worker_id: u64,
+    sequences: HashMap<u64, u64>, // The next sequence of each time period used so far
+}
+
+impl Backfill {
+    pub(crate) fn new(layout: Layout, worker_id: u64) -> Self {
+        Self {
+            layout,
+            worker_id,
+            sequences: HashMap::new(),
+        }
+    }
+
+    /// Generate a new Snowflake ID for the given timestamp.
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use twitter_snowflake::Snowflake;
+    /// let reserved_worker_id = 0;
+    /// let mut backfill = Snowflake::builder().with_worker_id(reserved_worker_id).build_backfill().unwrap();
+    /// let created_at = SystemTime::now() - Duration::from_secs(3600);
+    /// let id = backfill.generate_at(created_at).unwrap();
+    /// println!("Generated ID: {}", id);
+    /// ```
+    /// # Errors
+    /// Returns an error if the timestamp is before the epoch or in the future,
+    /// or if the sequence of its time period has been used up.
+    pub fn generate_at(&mut self, timestamp: SystemTime) -> Result<u64, SnowflakeError> {
+        if timestamp > SystemTime::now() {
+            return Err(SnowflakeError::TimestampInFuture { timestamp });
+        }
+        let ticks = self.layout.timestamp_at(timestamp)?;
+        if ticks >> self.layout.timestamp_bits > 0 {
+            return Err(SnowflakeError::EpochExhausted);
+        }
+
+        let max_sequence = (1u64 << self.layout.sequence_bits) - 1;
+        let next_sequence = self.sequences.entry(ticks).or_insert(0);
+        let sequence = *next_sequence;
+        if sequence > max_sequence {
+            return Err(SnowflakeError::SequenceExhausted { timestamp });
+        }
+        *next_sequence += 1;
+        self.layout.compose(ticks, self.worker_id, sequence)
+    }
+
+    /// Get the layout of the IDs produced by the generator.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
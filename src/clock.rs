@@ -0,0 +1,99 @@
+use std::{
+    fmt, io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A clock that caches the current time in an atomic, refreshed by a
+/// background ticker thread, so that reading it costs an atomic load instead
+/// of a system call.
+///
+/// The cached time lags the system clock by up to the resolution, plus any
+/// delay in scheduling the ticker thread. A generator given the clock with
+/// [`SnowflakeBuilder::with_clock`](crate::SnowflakeBuilder::with_clock) still
+/// reads the system clock around tick boundaries: while waiting for the next
+/// time period, and whenever the cached time falls behind its last timestamp.
+/// IDs therefore stay unique, and at worst carry a timestamp one resolution
+/// late.
+///
+/// Handles are cheap to clone and share one ticker thread, which stops once
+/// the last handle is dropped.
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use twitter_snowflake::{CoarseClock, Snowflake};
+/// let clock = CoarseClock::start(Duration::from_millis(1)).unwrap();
+/// let mut snowflake = Snowflake::builder().with_worker_id(1).with_clock(clock.clone()).build().unwrap();
+/// let id = snowflake.generate().unwrap();
+/// println!("Generated ID: {}", id);
+/// ```
+#[derive(Clone)]
+pub struct CoarseClock {
+    nanos: Arc<AtomicU64>, // The cached time in nanoseconds since the Unix epoch
+    resolution: Duration,  // The interval between refreshes
+}
+
+impl CoarseClock {
+    /// Start a ticker thread that refreshes the cached time every
+    /// `resolution`. A resolution no longer than the tick of the generators
+    /// keeps their timestamps close to the system clock.
+    /// # Errors
+    /// Returns an [`InvalidInput`](io::ErrorKind::InvalidInput) error if the
+    /// resolution is zero, which would spin the ticker thread, and otherwise
+    /// an error if the ticker thread cannot be spawned.
+    pub fn start(resolution: Duration) -> io::Result<Self> {
+        if resolution.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid resolution(=0ns), expected a resolution above zero",
+            ));
+        }
+        let nanos = Arc::new(AtomicU64::new(unix_nanos()));
+        let ticker = Arc::downgrade(&nanos);
+        thread::Builder::new().name("snowflake-clock".into()).spawn(move || {
+            // Stop once every handle has been dropped
+            while let Some(nanos) = ticker.upgrade() {
+                nanos.store(unix_nanos(), Ordering::Relaxed);
+                drop(nanos);
+                thread::sleep(resolution);
+            }
+        })?;
+        Ok(Self { nanos, resolution })
+    }
+
+    /// Get the interval between refreshes of the cached time.
+    pub fn resolution(&self) -> Duration {
+        self.resolution
+    }
+
+    /// Get the cached time.
+    pub fn now(&self) -> SystemTime {
+        UNIX_EPOCH + self.since_unix_epoch()
+    }
+
+    #[inline]
+    pub(crate) fn since_unix_epoch(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+}
+
+impl fmt::Debug for CoarseClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoarseClock")
+            .field("resolution", &self.resolution)
+            .field("now", &self.now())
+            .finish()
+    }
+}
+
+fn unix_nanos() -> u64 {
+    // A clock before the Unix epoch reads as the epoch, which the generators
+    // treat as a clock moved backwards
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_unix_epoch| since_unix_epoch.as_nanos() as u64)
+}
@@ -0,0 +1,263 @@
+use {
+    crate::{
+        sequencer::{Sequencer, Wait},
+        Epoch, Layout, SnowflakeError, TIMEOUT,
+    },
+    std::time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const EPOCH: Duration = Duration::from_secs(1704038400); // 2024-01-01 00:00:00
+
+/// The width of the IDs of a [`CompactSnowflake`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompactWidth {
+    /// IDs below 2^31, which fit signed 32-bit `INT` columns. The default
+    /// layout has 1 worker ID bit and 2 sequence bits. No tick and epoch make
+    /// 31 bits last long enough for every use, so both must be set.
+    Bits32,
+    /// IDs below 2^48. The default layout has 10-millisecond ticks, 4 worker
+    /// ID bits and 6 sequence bits, which last about 87 years.
+    Bits48,
+}
+
+impl CompactWidth {
+    /// Get the number of bits available to the fields.
+    const fn bits(self) -> u64 {
+        match self {
+            Self::Bits32 => 31,
+            Self::Bits48 => 48,
+        }
+    }
+}
+
+/// A generator of Snowflake IDs narrower than 64 bits, for devices and columns
+/// that cannot hold more.
+///
+/// The few timestamp bits left run out within years or decades, so choose the
+/// tick and epoch with care, and watch [`remaining_lifetime`](Self::remaining_lifetime).
+/// Once exhausted, the generator refuses to generate with
+/// [`EpochExhausted`](SnowflakeError::EpochExhausted).
+/// # Examples
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use twitter_snowflake::{CompactSnowflake, CompactWidth};
+/// let mut snowflake = CompactSnowflake::builder(CompactWidth::Bits32)
+///     .with_worker_id(1)
+///     .with_tick(Duration::from_secs(10))
+///     .with_sequence_bits(4)
+///     .with_epoch_time(SystemTime::now() - Duration::from_secs(86400))
+///     .build()
+///     .unwrap();
+/// let id = snowflake.generate().unwrap();
+/// assert!(id < 1 << 31);
+/// // 26 timestamp bits of 10 seconds last over 20 years
+/// assert!(snowflake.layout().plan().unwrap().lifetime > Duration::from_secs(20 * 365 * 86400));
+/// ```
+#[derive(Debug)]
+pub struct CompactSnowflake {
+    sequencer: Sequencer,      // The clock and sequence
+    worker_id: u64,            // The ID of the worker
+    timeout: Option<Duration>, // The timeout duration for waiting for the next time period
+    layout: Layout,            // The layout of the IDs
+}
+
+/// A builder for creating a compact Snowflake generator with custom
+/// configuration.
+pub struct CompactSnowflakeBuilder {
+    width: CompactWidth,
+    worker_id: u64,
+    worker_id_bits: u64,
+    sequence_bits: u64,
+    tick: Option<Duration>,
+    timeout: Option<Option<Duration>>,
+    epoch: Option<Epoch>,
+}
+
+impl CompactSnowflake {
+    /// Create a new compact Snowflake builder with the default configuration
+    /// of the width. [`CompactWidth::Bits32`] has no default tick and epoch.
+    pub fn builder(width: CompactWidth) -> CompactSnowflakeBuilder {
+        let (tick, epoch, worker_id_bits, sequence_bits) = match width {
+            CompactWidth::Bits32 => (None, None, 1, 2),
+            CompactWidth::Bits48 => (
+                Some(Duration::from_millis(10)),
+                Some(Epoch::Time(UNIX_EPOCH + EPOCH)),
+                4,
+                6,
+            ),
+        };
+        CompactSnowflakeBuilder {
+            width,
+            worker_id: 0,
+            worker_id_bits,
+            sequence_bits,
+            tick,
+            timeout: None,
+            epoch,
+        }
+    }
+
+    /// Generate a new compact Snowflake ID.
+    /// # Errors
+    /// Returns the same errors as [`Snowflake::generate`](crate::Snowflake::generate).
+    pub fn generate(&mut self) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Timeout(self.timeout))
+    }
+
+    /// Generate a new compact Snowflake ID without waiting, as
+    /// [`Snowflake::try_generate`](crate::Snowflake::try_generate) does.
+    pub fn try_generate(&mut self) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Never)
+    }
+
+    /// Generate a new compact Snowflake ID, waiting for the next time period
+    /// no later than the given deadline, as
+    /// [`Snowflake::generate_before`](crate::Snowflake::generate_before) does.
+    pub fn generate_before(&mut self, deadline: Instant) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Deadline(deadline))
+    }
+
+    fn next_id(&mut self, wait: Wait) -> Result<u64, SnowflakeError> {
+        let (timestamp, sequence) = self.sequencer.next(wait)?;
+        let sequence_bits = self.layout.sequence_bits;
+        let timestamp_shift = self.layout.worker_id_bits + sequence_bits;
+        Ok((timestamp << timestamp_shift) | (self.worker_id << sequence_bits) | sequence)
+    }
+
+    /// Get the time at which the generator runs out of timestamp bits, or
+    /// `None` if [`SystemTime`] cannot represent it.
+    pub fn exhaustion_time(&self) -> Option<SystemTime> {
+        self.sequencer.exhaustion_time()
+    }
+
+    /// Get the time left before the generator runs out of timestamp bits.
+    /// Returns [`Duration::ZERO`] once the epoch is exhausted, and
+    /// [`Duration::MAX`] if the exhaustion time is beyond [`SystemTime`].
+    pub fn remaining_lifetime(&self) -> Duration {
+        self.sequencer.remaining_lifetime()
+    }
+
+    /// Get the layout of the IDs produced by the generator.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+impl CompactSnowflakeBuilder {
+    /// Set the worker ID for the generator.
+    pub fn with_worker_id(mut self, worker_id: u64) -> Self {
+        self.worker_id = worker_id;
+        self
+    }
+
+    /// Set the number of bits used for the worker ID.
+    pub fn with_worker_id_bits(mut self, worker_id_bits: u64) -> Self {
+        self.worker_id_bits = worker_id_bits;
+        self
+    }
+
+    /// Set the number of bits used for the sequence. The timestamp takes the
+    /// bits left over by the worker ID and sequence.
+    pub fn with_sequence_bits(mut self, sequence_bits: u64) -> Self {
+        self.sequence_bits = sequence_bits;
+        self
+    }
+
+    /// Set the duration of one timestamp tick.
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = Some(tick);
+        self
+    }
+
+    /// Set the timeout duration for waiting for the next time period. The
+    /// default is one second or two ticks, whichever is longer.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Some(timeout));
+        self
+    }
+
+    /// Wait indefinitely for the next time period.
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = Some(None);
+        self
+    }
+
+    /// Set the epoch time, in ticks since the Unix epoch. Prefer
+    /// [`with_epoch_time`](Self::with_epoch_time), which does not depend on
+    /// the tick.
+    pub fn with_epoch(mut self, epoch: u64) -> Self {
+        self.epoch = Some(Epoch::Ticks(epoch));
+        self
+    }
+
+    /// Set the epoch time.
+    pub fn with_epoch_time(mut self, epoch: SystemTime) -> Self {
+        self.epoch = Some(Epoch::Time(epoch));
+        self
+    }
+
+    /// Build the compact Snowflake generator with the specified configuration.
+    /// # Errors
+    /// Returns the same errors as
+    /// [`SnowflakeBuilder::build`](crate::SnowflakeBuilder::build), an error
+    /// if the worker ID and sequence leave no bits for the timestamp, and an
+    /// error if the tick or epoch of [`CompactWidth::Bits32`] is not set.
+    /// ```
+    /// use twitter_snowflake::{CompactSnowflake, CompactWidth, SnowflakeError};
+    /// let snowflake = CompactSnowflake::builder(CompactWidth::Bits32)
+    ///     .with_worker_id_bits(16)
+    ///     .with_sequence_bits(15)
+    ///     .build();
+    /// assert_eq!(
+    ///     snowflake.err(),
+    ///     Some(SnowflakeError::InvalidSequenceBits { bits: 15, allowed: 1..=14 })
+    /// );
+    /// ```
+    pub fn build(self) -> Result<CompactSnowflake, SnowflakeError> {
+        // Every field needs at least one bit
+        let bits = self.width.bits();
+        let allowed = 1..=bits - 2;
+        if !allowed.contains(&self.worker_id_bits) {
+            return Err(SnowflakeError::InvalidWorkerIdBits {
+                bits: self.worker_id_bits,
+                allowed,
+            });
+        }
+        let allowed = 1..=bits - 1 - self.worker_id_bits;
+        if !allowed.contains(&self.sequence_bits) {
+            return Err(SnowflakeError::InvalidSequenceBits {
+                bits: self.sequence_bits,
+                allowed,
+            });
+        }
+        let max_worker_id = (1u64 << self.worker_id_bits) - 1;
+        if self.worker_id > max_worker_id {
+            return Err(SnowflakeError::InvalidWorkerId {
+                worker_id: self.worker_id,
+                allowed: 0..=max_worker_id,
+            });
+        }
+        let tick = self.tick.ok_or(SnowflakeError::MissingSetting { setting: "tick" })?;
+        if tick.is_zero() {
+            return Err(SnowflakeError::InvalidTick { tick });
+        }
+        let epoch = self.epoch.ok_or(SnowflakeError::MissingSetting { setting: "epoch" })?;
+        let epoch = epoch.resolve(tick)?;
+        // Waiting for the next time period takes up to a tick
+        let timeout = self.timeout.unwrap_or(Some(TIMEOUT.max(tick.saturating_mul(2))));
+
+        let timestamp_bits = bits - self.worker_id_bits - self.sequence_bits;
+        let layout = Layout::new(tick, timestamp_bits, self.worker_id_bits, self.sequence_bits, epoch);
+        Ok(CompactSnowflake {
+            sequencer: Sequencer::new(
+                tick,
+                epoch,
+                (1u64 << timestamp_bits) - 1,
+                (1u64 << self.sequence_bits) - 1,
+            ),
+            worker_id: self.worker_id,
+            timeout,
+            layout,
+        })
+    }
+}
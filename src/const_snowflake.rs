@@ -0,0 +1,114 @@
+use {
+    crate::{
+        sequencer::{Sequencer, Wait},
+        Layout, SnowflakeError, MAX_ADJUSTABLE_BITS, MIN_BITS, TIMESTAMP_BITS,
+    },
+    std::time::{Duration, Instant, SystemTime},
+};
+
+/// A Snowflake generator whose worker ID and sequence bits are fixed at
+/// compile time, so that shifts and masks are constants on the hot path.
+///
+/// The timestamp takes the bits left over by the worker ID and sequence, with
+/// the tick and epoch of [`Snowflake`](crate::Snowflake). An invalid bit
+/// allocation fails to compile. Build one from a
+/// [`SnowflakeBuilder`](crate::SnowflakeBuilder) with
+/// [`build_const`](crate::SnowflakeBuilder::build_const).
+/// # Examples
+/// ```
+/// use twitter_snowflake::{ConstSnowflake, Snowflake};
+/// let mut snowflake: ConstSnowflake<8, 14> = Snowflake::builder().with_worker_id(1).build_const().unwrap();
+/// let id = snowflake.generate().unwrap();
+/// assert_eq!((id >> 14) & 0xff, 1);
+/// ```
+/// ```compile_fail
+/// use twitter_snowflake::{ConstSnowflake, Snowflake};
+/// // No bits are left for the timestamp
+/// let snowflake: ConstSnowflake<32, 32> = Snowflake::builder().build_const().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ConstSnowflake<const WORKER_ID_BITS: u64, const SEQUENCE_BITS: u64> {
+    sequencer: Sequencer,      // The clock and sequence
+    worker_id: u64,            // The ID of the worker
+    timeout: Option<Duration>, // The timeout duration for waiting for the next time period
+}
+
+impl<const WORKER_ID_BITS: u64, const SEQUENCE_BITS: u64> ConstSnowflake<WORKER_ID_BITS, SEQUENCE_BITS> {
+    /// The number of bits used for the timestamp.
+    pub const TIMESTAMP_BITS: u64 = {
+        assert!(WORKER_ID_BITS >= MIN_BITS, "the worker ID needs at least one bit");
+        assert!(SEQUENCE_BITS >= MIN_BITS, "the sequence needs at least one bit");
+        assert!(
+            WORKER_ID_BITS + SEQUENCE_BITS < TIMESTAMP_BITS + MAX_ADJUSTABLE_BITS,
+            "the timestamp needs at least one bit"
+        );
+        TIMESTAMP_BITS + MAX_ADJUSTABLE_BITS - WORKER_ID_BITS - SEQUENCE_BITS
+    };
+    /// The largest worker ID.
+    pub const MAX_WORKER_ID: u64 = (1 << WORKER_ID_BITS) - 1;
+
+    pub(crate) const MAX_TIMESTAMP: u64 = (1 << Self::TIMESTAMP_BITS) - 1;
+    pub(crate) const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+    const WORKER_ID_SHIFT: u64 = SEQUENCE_BITS;
+    const TIMESTAMP_SHIFT: u64 = WORKER_ID_BITS + SEQUENCE_BITS;
+
+    pub(crate) fn new(sequencer: Sequencer, worker_id: u64, timeout: Option<Duration>) -> Self {
+        Self {
+            sequencer,
+            worker_id,
+            timeout,
+        }
+    }
+
+    /// Generate a new Snowflake ID.
+    /// # Errors
+    /// Returns the same errors as [`Snowflake::generate`](crate::Snowflake::generate).
+    pub fn generate(&mut self) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Timeout(self.timeout))
+    }
+
+    /// Generate a new Snowflake ID without waiting, as
+    /// [`Snowflake::try_generate`](crate::Snowflake::try_generate) does.
+    pub fn try_generate(&mut self) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Never)
+    }
+
+    /// Generate a new Snowflake ID, waiting for the next time period no later
+    /// than the given deadline, as
+    /// [`Snowflake::generate_before`](crate::Snowflake::generate_before) does.
+    pub fn generate_before(&mut self, deadline: Instant) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Deadline(deadline))
+    }
+
+    #[inline]
+    fn next_id(&mut self, wait: Wait) -> Result<u64, SnowflakeError> {
+        let (timestamp, sequence) = self
+            .sequencer
+            .next_within(wait, Self::MAX_TIMESTAMP, Self::MAX_SEQUENCE)?;
+        Ok((timestamp << Self::TIMESTAMP_SHIFT) | (self.worker_id << Self::WORKER_ID_SHIFT) | sequence)
+    }
+
+    /// Get the time at which the generator runs out of timestamp bits, or
+    /// `None` if [`SystemTime`] cannot represent it.
+    pub fn exhaustion_time(&self) -> Option<SystemTime> {
+        self.sequencer.exhaustion_time()
+    }
+
+    /// Get the time left before the generator runs out of timestamp bits.
+    /// Returns [`Duration::ZERO`] once the epoch is exhausted, and
+    /// [`Duration::MAX`] if the exhaustion time is beyond [`SystemTime`].
+    pub fn remaining_lifetime(&self) -> Duration {
+        self.sequencer.remaining_lifetime()
+    }
+
+    /// Get the layout of the IDs produced by the generator.
+    pub fn layout(&self) -> Layout {
+        Layout::new(
+            self.sequencer.tick,
+            Self::TIMESTAMP_BITS,
+            WORKER_ID_BITS,
+            SEQUENCE_BITS,
+            self.sequencer.epoch,
+        )
+    }
+}
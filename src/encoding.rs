@@ -0,0 +1,343 @@
+use {
+    crate::{SnowflakeError, SnowflakeId},
+    std::ops::RangeInclusive,
+};
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const BASE58: &[u8; 58] = b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// The number of characters needed for u64::MAX
+const CROCKFORD_LEN: usize = 13;
+const BASE58_LEN: usize = 11;
+const BASE62_LEN: usize = 11;
+const BASE64_URL_LEN: usize = 11;
+const DECIMAL_LEN: usize = 20;
+
+/// A compact string encoding of a [`SnowflakeId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum Encoding {
+    /// Crockford base32: digits and uppercase letters without `I`, `L`, `O`
+    /// and `U`. Parsing is case-insensitive.
+    Crockford,
+    /// Base58 with the alphabet of `bwmarrin/snowflake`, which leaves out the
+    /// look-alike characters `0`, `O`, `I` and `l`.
+    Base58,
+    /// Base62: digits, uppercase and lowercase letters.
+    Base62,
+    /// URL-safe base64 of the big-endian bytes, without padding.
+    Base64Url,
+    /// Crockford base32 zero-padded to 13 uppercase characters, so that
+    /// lexical order matches numeric order, as needed for string keys.
+    Sortable,
+}
+
+impl Encoding {
+    /// Encode an ID.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::Encoding;
+    /// assert_eq!(Encoding::Crockford.encode(1234567890), "14SC0PJ");
+    /// assert_eq!(Encoding::Base64Url.encode(1234567890), "AAAAAEmWAtI");
+    /// ```
+    pub fn encode(self, id: u64) -> String {
+        match self {
+            Self::Crockford => encode_positional(id, CROCKFORD),
+            Self::Base58 => encode_positional(id, BASE58),
+            Self::Base62 => encode_positional(id, BASE62),
+            Self::Sortable => format!("{:0>CROCKFORD_LEN$}", encode_positional(id, CROCKFORD)),
+            Self::Base64Url => {
+                // 64 bits take 11 characters of 6 bits, padded with 2 zero bits
+                let bits = (id as u128) << 2;
+                (0..BASE64_URL_LEN)
+                    .rev()
+                    .map(|i| BASE64_URL[(bits >> (6 * i)) as usize & 0x3f] as char)
+                    .collect()
+            }
+        }
+    }
+
+    /// Decode an ID, rejecting any character outside the alphabet.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::{Encoding, SnowflakeError};
+    /// assert_eq!(Encoding::Crockford.decode("14sc0pj"), Ok(1234567890));
+    /// assert_eq!(
+    ///     Encoding::Base58.decode("2Ol"),
+    ///     Err(SnowflakeError::InvalidCharacter { character: 'O', position: 1 })
+    /// );
+    /// ```
+    /// # Errors
+    /// Returns an error if the string is empty or too long, if it contains a
+    /// character outside the alphabet, or if the value overflows `u64`.
+    pub fn decode(self, s: &str) -> Result<u64, SnowflakeError> {
+        match self {
+            Self::Crockford => decode_positional(s, 32, CROCKFORD_LEN, |c| crockford_digit(c, false), |_| false),
+            Self::Base58 => decode_positional(s, 58, BASE58_LEN, |c| alphabet_digit(BASE58, c), |_| false),
+            Self::Base62 => decode_positional(s, 62, BASE62_LEN, |c| alphabet_digit(BASE62, c), |_| false),
+            Self::Sortable => {
+                // Only the canonical form keeps keys unique and ordered
+                let length = s.chars().count();
+                if length != CROCKFORD_LEN {
+                    return Err(SnowflakeError::InvalidLength {
+                        length,
+                        allowed: CROCKFORD_LEN..=CROCKFORD_LEN,
+                    });
+                }
+                decode_positional(s, 32, CROCKFORD_LEN, |c| alphabet_digit(CROCKFORD, c), |_| false)
+            }
+            Self::Base64Url => {
+                let length = s.chars().count();
+                if length != BASE64_URL_LEN {
+                    return Err(SnowflakeError::InvalidLength {
+                        length,
+                        allowed: BASE64_URL_LEN..=BASE64_URL_LEN,
+                    });
+                }
+                let mut bits = 0u128;
+                for (position, character) in s.chars().enumerate() {
+                    let digit = alphabet_digit(BASE64_URL, character)
+                        .ok_or(SnowflakeError::InvalidCharacter { character, position })?;
+                    bits = (bits << 6) | digit as u128;
+                }
+                // The padding bits must be zero, so each ID has a single encoding
+                if bits & 0b11 != 0 {
+                    let character = s.chars().last().unwrap_or_default();
+                    return Err(SnowflakeError::InvalidCharacter {
+                        character,
+                        position: BASE64_URL_LEN - 1,
+                    });
+                }
+                Ok((bits >> 2) as u64)
+            }
+        }
+    }
+
+    /// Encode an ID followed by a check character, computed with the Luhn mod N
+    /// algorithm over the alphabet, to catch IDs mistyped by hand.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::{Encoding, SnowflakeError};
+    /// assert_eq!(Encoding::Crockford.encode_checked(1234567890), "14SC0PJ0");
+    /// assert_eq!(Encoding::Crockford.decode_checked("14SC0PJ0"), Ok(1234567890));
+    /// assert_eq!(Encoding::Crockford.decode_checked("14CS0PJ0"), Err(SnowflakeError::ChecksumMismatch));
+    /// ```
+    pub fn encode_checked(self, id: u64) -> String {
+        let mut encoded = self.encode(id);
+        let digits = encoded.chars().map(|c| self.digit(c).unwrap_or_default());
+        let check = luhn_check(digits, self.alphabet().len() as u64);
+        encoded.push(self.alphabet()[check as usize] as char);
+        encoded
+    }
+
+    /// Decode an ID followed by a check character. The check detects any single
+    /// mistyped character, and any swap of adjacent characters except of the
+    /// first and last characters of the alphabet.
+    /// # Errors
+    /// Returns an error if the string is not a valid encoding of an ID followed
+    /// by a check character, or if the check character does not match.
+    pub fn decode_checked(self, s: &str) -> Result<u64, SnowflakeError> {
+        let mut digits = Vec::new();
+        for (position, character) in s.chars().enumerate() {
+            let digit = self
+                .digit(character)
+                .ok_or(SnowflakeError::InvalidCharacter { character, position })?;
+            digits.push(digit);
+        }
+        // The check character adds one to the length
+        let lengths = self.lengths();
+        let allowed = lengths.start() + 1..=lengths.end() + 1;
+        let Some(check) = digits.pop() else {
+            return Err(SnowflakeError::InvalidLength { length: 0, allowed });
+        };
+        if luhn_check(digits.iter().copied(), self.alphabet().len() as u64) != check {
+            return Err(SnowflakeError::ChecksumMismatch);
+        }
+        let (encoded, _) = s.split_at(s.len() - s.chars().last().map_or(0, char::len_utf8));
+        self.decode(encoded).map_err(|err| match err {
+            SnowflakeError::InvalidLength { length, .. } => SnowflakeError::InvalidLength {
+                length: length + 1,
+                allowed,
+            },
+            err => err,
+        })
+    }
+
+    /// Decode a Crockford base32 ID, tolerating common typos: `I` and `L` read
+    /// as `1`, `O` reads as `0`, and hyphens are ignored.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::Encoding;
+    /// assert_eq!(Encoding::decode_crockford_lenient("14SC-OPJ"), Ok(1234567890));
+    /// ```
+    /// # Errors
+    /// Returns an error if the string is empty or too long, if it contains a
+    /// character outside the alphabet, or if the value overflows `u64`.
+    pub fn decode_crockford_lenient(s: &str) -> Result<u64, SnowflakeError> {
+        decode_positional(s, 32, CROCKFORD_LEN, |c| crockford_digit(c, true), |c| c == '-')
+    }
+}
+
+impl Encoding {
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            Self::Crockford | Self::Sortable => CROCKFORD,
+            Self::Base58 => BASE58,
+            Self::Base62 => BASE62,
+            Self::Base64Url => BASE64_URL,
+        }
+    }
+
+    fn lengths(self) -> RangeInclusive<usize> {
+        match self {
+            Self::Crockford => 1..=CROCKFORD_LEN,
+            Self::Sortable => CROCKFORD_LEN..=CROCKFORD_LEN,
+            Self::Base58 => 1..=BASE58_LEN,
+            Self::Base62 => 1..=BASE62_LEN,
+            Self::Base64Url => BASE64_URL_LEN..=BASE64_URL_LEN,
+        }
+    }
+
+    fn digit(self, character: char) -> Option<u64> {
+        match self {
+            Self::Crockford => crockford_digit(character, false),
+            _ => alphabet_digit(self.alphabet(), character),
+        }
+    }
+}
+
+impl<T> SnowflakeId<T> {
+    /// Encode the ID with the given encoding.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::{Encoding, SnowflakeId};
+    /// let id = SnowflakeId::new(1234567890);
+    /// assert_eq!(id.encode(Encoding::Base62), "1LY7VK");
+    /// ```
+    pub fn encode(self, encoding: Encoding) -> String {
+        encoding.encode(self.get())
+    }
+
+    /// Encode the ID with the given encoding, followed by a check character.
+    pub fn encode_checked(self, encoding: Encoding) -> String {
+        encoding.encode_checked(self.get())
+    }
+
+    /// Encode the ID as 8 big-endian bytes, whose lexical order matches
+    /// numeric order, as needed for byte keys.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::SnowflakeId;
+    /// let (a, b) = (SnowflakeId::new(255), SnowflakeId::new(256));
+    /// assert!(a.to_sortable_bytes() < b.to_sortable_bytes());
+    /// ```
+    pub const fn to_sortable_bytes(self) -> [u8; 8] {
+        self.get().to_be_bytes()
+    }
+}
+
+impl SnowflakeId {
+    /// Decode an ID with the given encoding.
+    /// # Errors
+    /// Returns an error if the string is not a valid encoding of an ID.
+    pub fn decode(s: &str, encoding: Encoding) -> Result<Self, SnowflakeError> {
+        encoding.decode(s).map(Self::new)
+    }
+
+    /// Decode an ID with the given encoding, followed by a check character.
+    /// # Errors
+    /// Returns an error if the string is not a valid encoding of an ID, or if
+    /// the check character does not match.
+    pub fn decode_checked(s: &str, encoding: Encoding) -> Result<Self, SnowflakeError> {
+        encoding.decode_checked(s).map(Self::new)
+    }
+
+    /// Decode an ID from 8 big-endian bytes.
+    pub const fn from_sortable_bytes(bytes: [u8; 8]) -> Self {
+        Self::new(u64::from_be_bytes(bytes))
+    }
+}
+
+/// Decode a decimal ID.
+pub(crate) fn decode_decimal(s: &str) -> Result<u64, SnowflakeError> {
+    decode_positional(s, 10, DECIMAL_LEN, |c| c.to_digit(10).map(u64::from), |_| false)
+}
+
+/// Encode a number most significant digit first, without leading zeros.
+fn encode_positional(mut id: u64, alphabet: &[u8]) -> String {
+    let base = alphabet.len() as u64;
+    let mut digits = Vec::new();
+    loop {
+        digits.push(alphabet[(id % base) as usize]);
+        id /= base;
+        if id == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().map(|&digit| digit as char).collect()
+}
+
+/// Decode a number most significant digit first, ignoring skipped characters.
+fn decode_positional(
+    s: &str,
+    base: u64,
+    max_length: usize,
+    digit: impl Fn(char) -> Option<u64>,
+    skip: impl Fn(char) -> bool,
+) -> Result<u64, SnowflakeError> {
+    let mut id = 0u64;
+    let mut length = 0;
+    for (position, character) in s.chars().enumerate() {
+        if skip(character) {
+            continue;
+        }
+        let digit = digit(character).ok_or(SnowflakeError::InvalidCharacter { character, position })?;
+        length += 1;
+        if length > max_length {
+            // Keep counting to report the full length
+            continue;
+        }
+        id = id
+            .checked_mul(base)
+            .and_then(|id| id.checked_add(digit))
+            .ok_or(SnowflakeError::IdOverflow)?;
+    }
+    if !(1..=max_length).contains(&length) {
+        return Err(SnowflakeError::InvalidLength {
+            length,
+            allowed: 1..=max_length,
+        });
+    }
+    Ok(id)
+}
+
+/// Compute the Luhn mod N check digit of digits given most significant first.
+/// Ref: https://en.wikipedia.org/wiki/Luhn_mod_N_algorithm
+fn luhn_check(digits: impl DoubleEndedIterator<Item = u64>, base: u64) -> u64 {
+    let sum: u64 = digits
+        .rev()
+        .zip([2, 1].into_iter().cycle())
+        .map(|(digit, factor)| {
+            let addend = digit * factor;
+            addend / base + addend % base
+        })
+        .sum();
+    (base - sum % base) % base
+}
+
+/// Look up a digit in an alphabet.
+fn alphabet_digit(alphabet: &[u8], character: char) -> Option<u64> {
+    let position = alphabet.iter().position(|&c| c as char == character)?;
+    Some(position as u64)
+}
+
+/// Look up a Crockford base32 digit, case-insensitively.
+fn crockford_digit(character: char, lenient: bool) -> Option<u64> {
+    match character.to_ascii_uppercase() {
+        'I' | 'L' if lenient => Some(1),
+        'O' if lenient => Some(0),
+        character => alphabet_digit(CROCKFORD, character),
+    }
+}
@@ -0,0 +1,294 @@
+use {
+    crate::{encoding, Encoding, Layout, SnowflakeError},
+    std::{cmp::Ordering, fmt, hash, marker::PhantomData, str::FromStr, time::SystemTime},
+};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A Snowflake ID.
+///
+/// The ID itself does not know its [`Layout`], so decoding methods take the
+/// layout of the generator that produced it.
+///
+/// The type parameter marks the kind of [`Entity`] the ID refers to, so that
+/// IDs of different entities cannot be mixed up. It defaults to `()`, an
+/// untyped ID.
+/// # Examples
+/// ```
+/// use twitter_snowflake::{Snowflake, SnowflakeId};
+/// let mut snowflake = Snowflake::new(1).unwrap();
+/// let id = SnowflakeId::from(snowflake.generate().unwrap());
+/// assert_eq!(id.worker_id(&snowflake.layout()), 1);
+/// println!("Generated at: {}", id.to_rfc3339(&snowflake.layout()).unwrap());
+/// ```
+pub struct SnowflakeId<T = ()> {
+    id: u64,
+    entity: PhantomData<fn() -> T>,
+}
+
+/// A kind of entity identified by a [`SnowflakeId`].
+///
+/// An entity with a prefix displays and parses its IDs as the prefix, an
+/// underscore and the ID in its encoding, such as `ord_8M0kX`. Without a prefix
+/// IDs display and parse as decimal numbers.
+/// # Examples
+/// ```
+/// use twitter_snowflake::{Entity, SnowflakeError, SnowflakeId};
+/// struct Order;
+/// impl Entity for Order {
+///     const PREFIX: Option<&'static str> = Some("ord");
+/// }
+/// struct User;
+/// impl Entity for User {
+///     const PREFIX: Option<&'static str> = Some("usr");
+/// }
+/// let id = SnowflakeId::new(1234567890).cast::<Order>();
+/// assert_eq!(id.to_string(), "ord_1LY7VK");
+/// assert_eq!("ord_1LY7VK".parse(), Ok(id));
+/// assert_eq!(
+///     "ord_1LY7VK".parse::<SnowflakeId<User>>(),
+///     Err(SnowflakeError::PrefixMismatch { expected: "usr" })
+/// );
+/// ```
+pub trait Entity {
+    /// The prefix of the string form of IDs, without the underscore.
+    const PREFIX: Option<&'static str> = None;
+    /// The encoding of the string form of IDs after the prefix.
+    const ENCODING: Encoding = Encoding::Base62;
+}
+
+impl Entity for () {}
+
+impl SnowflakeId {
+    /// Create a new Snowflake ID from its integer value.
+    pub const fn new(id: u64) -> Self {
+        Self {
+            id,
+            entity: PhantomData,
+        }
+    }
+}
+
+impl<T> SnowflakeId<T> {
+    /// Convert the ID into an ID of another entity with the same value.
+    pub const fn cast<U>(self) -> SnowflakeId<U> {
+        SnowflakeId {
+            id: self.id,
+            entity: PhantomData,
+        }
+    }
+
+    /// Get the integer value of the ID.
+    pub const fn get(self) -> u64 {
+        self.id
+    }
+
+    /// Get the number of ticks from the epoch to the creation of the ID.
+    pub const fn ticks(self, layout: &Layout) -> u64 {
+        (self.id >> (layout.worker_id_bits + layout.sequence_bits)) & ((1u64 << layout.timestamp_bits) - 1)
+    }
+
+    /// Get the ID of the worker that generated the ID.
+    pub const fn worker_id(self, layout: &Layout) -> u64 {
+        (self.id >> layout.sequence_bits) & ((1u64 << layout.worker_id_bits) - 1)
+    }
+
+    /// Get the sequence of the ID within its time period.
+    pub const fn sequence(self, layout: &Layout) -> u64 {
+        self.id & ((1u64 << layout.sequence_bits) - 1)
+    }
+
+    /// Get the creation time of the ID, honouring the epoch and tick of the
+    /// layout, or `None` if [`SystemTime`] cannot represent it.
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use twitter_snowflake::{Snowflake, SnowflakeId};
+    /// let mut snowflake = Snowflake::new(1).unwrap();
+    /// let id = SnowflakeId::from(snowflake.generate().unwrap());
+    /// let age = SystemTime::now().duration_since(id.timestamp(&snowflake.layout()).unwrap()).unwrap();
+    /// assert!(age < Duration::from_secs(2));
+    /// ```
+    pub fn timestamp(self, layout: &Layout) -> Option<SystemTime> {
+        layout.time_at(self.ticks(layout))
+    }
+
+    /// Get the creation time of the ID as a `chrono` date time, or `None` if
+    /// `chrono` cannot represent it.
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(self, layout: &Layout) -> Option<chrono::DateTime<chrono::Utc>> {
+        let since_unix_epoch = layout.since_unix_epoch(self.ticks(layout));
+        let secs = i64::try_from(since_unix_epoch.as_secs()).ok()?;
+        chrono::DateTime::from_timestamp(secs, since_unix_epoch.subsec_nanos())
+    }
+
+    /// Get the creation time of the ID as a `time` date time, or `None` if
+    /// `time` cannot represent it.
+    #[cfg(feature = "time")]
+    pub fn to_offset_date_time(self, layout: &Layout) -> Option<time::OffsetDateTime> {
+        let nanos = layout.since_unix_epoch(self.ticks(layout)).as_nanos();
+        time::OffsetDateTime::from_unix_timestamp_nanos(i128::try_from(nanos).ok()?).ok()
+    }
+
+    /// Format the creation time of the ID as RFC 3339 in UTC, with as many
+    /// fractional digits as the tick of the layout needs, or `None` if
+    /// [`SystemTime`] cannot represent it.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use twitter_snowflake::{Layout, SnowflakeId};
+    /// let epoch = 1704067200000; // 2024-01-01 00:00:00.000 UTC
+    /// let layout = Layout::new(Duration::from_millis(1), 41, 10, 12, epoch);
+    /// let id = SnowflakeId::new(layout.compose(1500, 1, 0).unwrap());
+    /// assert_eq!(id.to_rfc3339(&layout).as_deref(), Some("2024-01-01T00:00:01.500Z"));
+    /// ```
+    pub fn to_rfc3339(self, layout: &Layout) -> Option<String> {
+        let since_unix_epoch = self.timestamp(layout)?.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+        let secs = since_unix_epoch.as_secs();
+        let (year, month, day) = civil_from_days(secs / SECS_PER_DAY);
+        let secs_of_day = secs % SECS_PER_DAY;
+        let (hour, minute, second) = (secs_of_day / 3600, secs_of_day / 60 % 60, secs_of_day % 60);
+        let nanos = since_unix_epoch.subsec_nanos();
+        let fraction = if layout.tick.subsec_nanos() == 0 {
+            String::new()
+        } else if layout.tick.subsec_nanos() % 1_000_000 == 0 {
+            format!(".{:03}", nanos / 1_000_000)
+        } else if layout.tick.subsec_nanos() % 1_000 == 0 {
+            format!(".{:06}", nanos / 1_000)
+        } else {
+            format!(".{nanos:09}")
+        };
+        Some(format!(
+            "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}{fraction}Z"
+        ))
+    }
+}
+
+/// Convert days since the Unix epoch into a (year, month, day) date.
+/// Ref: https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+impl From<u64> for SnowflakeId {
+    fn from(id: u64) -> Self {
+        Self::new(id)
+    }
+}
+
+impl<T> From<SnowflakeId<T>> for u64 {
+    fn from(id: SnowflakeId<T>) -> Self {
+        id.id
+    }
+}
+
+impl<T> TryFrom<SnowflakeId<T>> for i64 {
+    type Error = SnowflakeError;
+
+    /// Convert the ID into a signed integer.
+    /// # Errors
+    /// Returns [`NegativeId`](SnowflakeError::NegativeId) if the ID uses the
+    /// sign bit.
+    fn try_from(id: SnowflakeId<T>) -> Result<Self, Self::Error> {
+        i64::try_from(id.id).map_err(|_| SnowflakeError::NegativeId { id: id.id as i64 })
+    }
+}
+
+impl TryFrom<i64> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    /// Convert a signed integer into an ID.
+    /// # Errors
+    /// Returns [`NegativeId`](SnowflakeError::NegativeId) if the integer is
+    /// negative.
+    fn try_from(id: i64) -> Result<Self, Self::Error> {
+        u64::try_from(id)
+            .map(Self::new)
+            .map_err(|_| SnowflakeError::NegativeId { id })
+    }
+}
+
+// Manual impls, since derives would require the entity to implement the
+// traits too
+
+impl<T> fmt::Debug for SnowflakeId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SnowflakeId").field(&self.id).finish()
+    }
+}
+
+impl<T> Clone for SnowflakeId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SnowflakeId<T> {}
+
+impl<T> Default for SnowflakeId<T> {
+    fn default() -> Self {
+        SnowflakeId::new(0).cast()
+    }
+}
+
+impl<T> PartialEq for SnowflakeId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for SnowflakeId<T> {}
+
+impl<T> PartialOrd for SnowflakeId<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for SnowflakeId<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<T> hash::Hash for SnowflakeId<T> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T: Entity> fmt::Display for SnowflakeId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match T::PREFIX {
+            Some(prefix) => write!(f, "{prefix}_{}", T::ENCODING.encode(self.id)),
+            None => fmt::Display::fmt(&self.id, f),
+        }
+    }
+}
+
+impl<T: Entity> FromStr for SnowflakeId<T> {
+    type Err = SnowflakeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = match T::PREFIX {
+            Some(prefix) => {
+                let encoded = s
+                    .strip_prefix(prefix)
+                    .and_then(|s| s.strip_prefix('_'))
+                    .ok_or(SnowflakeError::PrefixMismatch { expected: prefix })?;
+                T::ENCODING.decode(encoded)?
+            }
+            None => encoding::decode_decimal(s)?,
+        };
+        Ok(SnowflakeId::new(id).cast())
+    }
+}
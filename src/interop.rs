@@ -0,0 +1,119 @@
+use {
+    crate::{Layout, SnowflakeError, SnowflakeId},
+    std::time::Duration,
+};
+
+// UUIDs and ULIDs as big-endian 128-bit integers
+const VERSION_SHIFT: u32 = 76;
+const VERSION_MASK: u128 = 0xf << VERSION_SHIFT;
+const VARIANT: u128 = 0b10 << 62;
+const VARIANT_MASK: u128 = 0b11 << 62;
+const UNIX_MILLIS_SHIFT: u32 = 80;
+const UNIX_MILLIS_MASK: u128 = (1 << 48) - 1;
+
+impl<T> SnowflakeId<T> {
+    /// Embed the ID in a UUIDv8, whose byte order matches the order of IDs.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::SnowflakeId;
+    /// let id = SnowflakeId::new(0x0123_4567_89ab_cdef);
+    /// assert_eq!(id.to_uuid_v8(), 0x0123_4567_89ab_8cde_bc00_0000_0000_0000);
+    /// assert_eq!(SnowflakeId::from_uuid(id.to_uuid_v8()), Ok(id));
+    /// ```
+    pub const fn to_uuid_v8(self) -> u128 {
+        let id = self.get() as u128;
+        (id >> 16) << 80 | 8 << VERSION_SHIFT | ((id >> 4) & 0xfff) << 64 | VARIANT | (id & 0xf) << 58
+    }
+
+    /// Embed the ID in a UUIDv7, which starts with the creation time of the ID
+    /// in Unix milliseconds as UUIDv7 requires, so it sorts among other UUIDv7s
+    /// by time, and keeps the order of IDs of the layout.
+    pub fn to_uuid_v7(self, layout: &Layout) -> u128 {
+        let id = self.get() as u128;
+        unix_millis(layout.since_unix_epoch(self.ticks(layout))) << UNIX_MILLIS_SHIFT
+            | 7 << VERSION_SHIFT
+            | (id >> 52) << 64
+            | VARIANT
+            | (id & ((1 << 52) - 1)) << 10
+    }
+
+    /// Embed the ID in a ULID, which starts with the creation time of the ID in
+    /// Unix milliseconds, and keeps the order of IDs of the layout.
+    pub fn to_ulid(self, layout: &Layout) -> u128 {
+        unix_millis(layout.since_unix_epoch(self.ticks(layout))) << UNIX_MILLIS_SHIFT | (self.get() as u128) << 16
+    }
+
+    /// Embed the ID in a `ulid` ULID, as [`to_ulid`](Self::to_ulid) does.
+    #[cfg(feature = "ulid")]
+    pub fn to_ulid_value(self, layout: &Layout) -> ulid::Ulid {
+        ulid::Ulid(self.to_ulid(layout))
+    }
+}
+
+impl SnowflakeId {
+    /// Extract an ID embedded in a UUIDv8 or UUIDv7.
+    ///
+    /// This is only an inverse for UUIDs made by
+    /// [`to_uuid_v8`](Self::to_uuid_v8) or [`to_uuid_v7`](Self::to_uuid_v7):
+    /// the UUIDs carry no marker, so any UUIDv8 or UUIDv7 whose unused low
+    /// bits are zero yields an ID, meaningless if it came from elsewhere. Of a
+    /// UUIDv7, only the bits of the ID are read back, not its Unix
+    /// milliseconds.
+    /// # Errors
+    /// Returns an error if the UUID is of another version or variant, or if
+    /// its unused low bits are not zero.
+    pub const fn from_uuid(uuid: u128) -> Result<Self, SnowflakeError> {
+        if uuid & VARIANT_MASK != VARIANT {
+            return Err(SnowflakeError::NoEmbeddedId);
+        }
+        let id = match (uuid & VERSION_MASK) >> VERSION_SHIFT {
+            8 if uuid & ((1 << 58) - 1) == 0 => (uuid >> 80) << 16 | ((uuid >> 64) & 0xfff) << 4 | (uuid >> 58) & 0xf,
+            7 if uuid & ((1 << 10) - 1) == 0 => ((uuid >> 64) & 0xfff) << 52 | (uuid >> 10) & ((1 << 52) - 1),
+            _ => return Err(SnowflakeError::NoEmbeddedId),
+        };
+        Ok(Self::new(id as u64))
+    }
+
+    /// Extract an ID embedded in a ULID.
+    ///
+    /// This is only an inverse for ULIDs made by [`to_ulid`](Self::to_ulid):
+    /// the ULIDs carry no marker, so any ULID whose low 16 bits are zero yields
+    /// an ID, meaningless if it came from elsewhere.
+    /// # Errors
+    /// Returns an error if the low 16 bits of the ULID are not zero.
+    pub const fn from_ulid(ulid: u128) -> Result<Self, SnowflakeError> {
+        if ulid & 0xffff != 0 {
+            return Err(SnowflakeError::NoEmbeddedId);
+        }
+        Ok(Self::new((ulid >> 16) as u64))
+    }
+}
+
+fn unix_millis(since_unix_epoch: Duration) -> u128 {
+    since_unix_epoch.as_millis() & UNIX_MILLIS_MASK
+}
+
+#[cfg(feature = "uuid")]
+impl<T> From<SnowflakeId<T>> for uuid::Uuid {
+    fn from(id: SnowflakeId<T>) -> Self {
+        Self::from_u128(id.to_uuid_v8())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl TryFrom<uuid::Uuid> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(uuid: uuid::Uuid) -> Result<Self, Self::Error> {
+        Self::from_uuid(uuid.as_u128())
+    }
+}
+
+#[cfg(feature = "ulid")]
+impl TryFrom<ulid::Ulid> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(ulid: ulid::Ulid) -> Result<Self, Self::Error> {
+        Self::from_ulid(ulid.0)
+    }
+}
@@ -0,0 +1,424 @@
+use {
+    crate::{SnowflakeError, MAX_ADJUSTABLE_BITS, TIMESTAMP_BITS, WORKER_ID_BITS},
+    std::{
+        fmt,
+        ops::RangeInclusive,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+// Every field needs at least one bit, which caps the others at 62
+const MIN_FIELD_BITS: u64 = 1;
+const MAX_FIELD_BITS: u64 = 64 - 2 * MIN_FIELD_BITS;
+const SECS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+// Thresholds below which a layout is considered unreasonable
+const MIN_IDS_PER_TICK: u64 = 64;
+const MIN_IDS_PER_SECOND: u64 = 1000;
+const MIN_LIFETIME: Duration = Duration::from_secs(10 * SECS_PER_YEAR);
+
+/// The bit allocation and time base of a Snowflake ID.
+///
+/// A layout describes how an ID is split into timestamp, worker ID and
+/// sequence fields, how long one timestamp tick lasts and where time starts
+/// counting. [`Layout::default`] returns the layout used by
+/// [`Snowflake::new`](crate::Snowflake::new). Deserializing rejects layouts
+/// whose fields do not fit in 64 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawLayout"))]
+pub struct Layout {
+    /// The duration of one timestamp tick.
+    pub tick: Duration,
+    /// The number of bits used for the timestamp.
+    pub timestamp_bits: u64,
+    /// The number of bits used for the worker ID.
+    pub worker_id_bits: u64,
+    /// The number of bits used for the sequence.
+    pub sequence_bits: u64,
+    /// The epoch, in ticks since the Unix epoch.
+    pub epoch: u64,
+}
+
+/// A layout as deserialized, before validation.
+#[cfg(feature = "serde")]
+#[derive(::serde::Deserialize)]
+struct RawLayout {
+    tick: Duration,
+    timestamp_bits: u64,
+    worker_id_bits: u64,
+    sequence_bits: u64,
+    epoch: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawLayout> for Layout {
+    type Error = SnowflakeError;
+
+    fn try_from(raw: RawLayout) -> Result<Self, Self::Error> {
+        let layout = Layout::new(
+            raw.tick,
+            raw.timestamp_bits,
+            raw.worker_id_bits,
+            raw.sequence_bits,
+            raw.epoch,
+        );
+        layout.validate()?;
+        Ok(layout)
+    }
+}
+
+/// The capacity of a [`Layout`], as reported by [`Layout::plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityPlan {
+    /// The number of distinct worker IDs.
+    pub max_workers: u64,
+    /// The number of IDs a single worker can generate within one tick.
+    pub max_ids_per_tick: u64,
+    /// The number of IDs a single worker can generate within one second.
+    pub max_ids_per_second: u64,
+    /// The time from the epoch until the timestamp bits run out.
+    pub lifetime: Duration,
+    /// The time at which the timestamp bits run out, if representable.
+    pub end: Option<SystemTime>,
+    /// The unreasonable aspects of the layout, if any.
+    pub warnings: Vec<CapacityWarning>,
+}
+
+/// An unreasonable, yet valid, aspect of a [`Layout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapacityWarning {
+    /// So few IDs fit in one tick that bursts keep waiting for the next tick.
+    NarrowSequence { max_ids_per_tick: u64 },
+    /// A single worker cannot sustain a useful rate of IDs.
+    LowThroughput { max_ids_per_second: u64 },
+    /// The timestamp bits run out too soon after the epoch.
+    ShortLifetime { lifetime: Duration },
+    /// The epoch lies in the future.
+    EpochInFuture,
+    /// The timestamp bits have already run out.
+    Exhausted,
+    /// The IDs use all 64 bits and are negative when read as `i64`.
+    SignBitUsed,
+}
+
+impl Layout {
+    /// Create a new layout.
+    /// # Parameters
+    /// - `tick`: The duration of one timestamp tick.
+    /// - `timestamp_bits`: The number of bits used for the timestamp.
+    /// - `worker_id_bits`: The number of bits used for the worker ID.
+    /// - `sequence_bits`: The number of bits used for the sequence.
+    /// - `epoch`: The epoch, in ticks since the Unix epoch.
+    pub const fn new(tick: Duration, timestamp_bits: u64, worker_id_bits: u64, sequence_bits: u64, epoch: u64) -> Self {
+        Self {
+            tick,
+            timestamp_bits,
+            worker_id_bits,
+            sequence_bits,
+            epoch,
+        }
+    }
+
+    /// Report the capacity of the layout.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use twitter_snowflake::{CapacityWarning, Layout};
+    /// let layout = Layout::new(Duration::from_millis(1), 41, 21, 1, 1704038400000);
+    /// let plan = layout.plan().unwrap();
+    /// assert_eq!(plan.max_workers, 1 << 21);
+    /// assert_eq!(plan.max_ids_per_tick, 2);
+    /// assert!(plan.warnings.contains(&CapacityWarning::NarrowSequence { max_ids_per_tick: 2 }));
+    /// ```
+    /// # Errors
+    /// Returns an error if the tick is zero, if a field has no bits, or if
+    /// the fields need more than 64 bits.
+    /// ```
+    /// use std::time::Duration;
+    /// use twitter_snowflake::Layout;
+    /// let layout = Layout::new(Duration::from_millis(1), 41, 12, 12, 1704038400000);
+    /// assert!(layout.plan().is_err());
+    /// ```
+    pub fn plan(&self) -> Result<CapacityPlan, SnowflakeError> {
+        self.validate()?;
+
+        let max_workers = 1u64 << self.worker_id_bits;
+        let max_ids_per_tick = 1u64 << self.sequence_bits;
+        let max_ids_per_second = (max_ids_per_tick as u128 * NANOS_PER_SEC / self.tick.as_nanos())
+            .try_into()
+            .unwrap_or(u64::MAX);
+        let lifetime = self.ticks(1u64 << self.timestamp_bits);
+        let start = UNIX_EPOCH.checked_add(self.ticks(self.epoch));
+        let end = start.and_then(|start| start.checked_add(lifetime));
+
+        let mut warnings = Vec::new();
+        if max_ids_per_tick < MIN_IDS_PER_TICK {
+            warnings.push(CapacityWarning::NarrowSequence { max_ids_per_tick });
+        }
+        if max_ids_per_second < MIN_IDS_PER_SECOND {
+            warnings.push(CapacityWarning::LowThroughput { max_ids_per_second });
+        }
+        if lifetime < MIN_LIFETIME {
+            warnings.push(CapacityWarning::ShortLifetime { lifetime });
+        }
+        let now = SystemTime::now();
+        if start.is_none_or(|start| start > now) {
+            warnings.push(CapacityWarning::EpochInFuture);
+        }
+        if end.is_some_and(|end| end <= now) {
+            warnings.push(CapacityWarning::Exhausted);
+        }
+        if self.timestamp_bits + self.worker_id_bits + self.sequence_bits == 64 {
+            warnings.push(CapacityWarning::SignBitUsed);
+        }
+
+        Ok(CapacityPlan {
+            max_workers,
+            max_ids_per_tick,
+            max_ids_per_second,
+            lifetime,
+            end,
+            warnings,
+        })
+    }
+
+    /// Compose an ID from its parts, mirroring
+    /// [`Snowflake::generate`](crate::Snowflake::generate).
+    /// # Parameters
+    /// - `timestamp`: The number of ticks since the epoch.
+    /// - `worker_id`: The ID of the worker.
+    /// - `sequence`: The sequence within the time period.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use twitter_snowflake::Layout;
+    /// const LAYOUT: Layout = Layout::new(Duration::from_millis(1), 41, 10, 12, 1704038400000);
+    /// const ID: u64 = match LAYOUT.compose(1, 2, 3) {
+    ///     Ok(id) => id,
+    ///     Err(_) => panic!("invalid parts"),
+    /// };
+    /// assert_eq!(ID, (1 << 22) | (2 << 12) | 3);
+    /// ```
+    /// # Errors
+    /// Returns an error if the layout is invalid, or if a part does not fit
+    /// in its bits.
+    /// ```
+    /// use twitter_snowflake::{Layout, SnowflakeError};
+    /// let layout = Layout::default();
+    /// let worker_id = 1 << layout.worker_id_bits;
+    /// assert!(matches!(layout.compose(1, worker_id, 0), Err(SnowflakeError::InvalidWorkerId { .. })));
+    /// ```
+    pub const fn compose(&self, timestamp: u64, worker_id: u64, sequence: u64) -> Result<u64, SnowflakeError> {
+        if let Err(e) = self.validate() {
+            return Err(e);
+        }
+        let max_timestamp = (1u64 << self.timestamp_bits) - 1;
+        if timestamp > max_timestamp {
+            return Err(SnowflakeError::InvalidTimestamp {
+                timestamp,
+                allowed: 0..=max_timestamp,
+            });
+        }
+        let max_worker_id = (1u64 << self.worker_id_bits) - 1;
+        if worker_id > max_worker_id {
+            return Err(SnowflakeError::InvalidWorkerId {
+                worker_id,
+                allowed: 0..=max_worker_id,
+            });
+        }
+        let max_sequence = (1u64 << self.sequence_bits) - 1;
+        if sequence > max_sequence {
+            return Err(SnowflakeError::InvalidSequence {
+                sequence,
+                allowed: 0..=max_sequence,
+            });
+        }
+        let worker_id_shift = self.sequence_bits;
+        let timestamp_shift = self.worker_id_bits + self.sequence_bits;
+        Ok((timestamp << timestamp_shift) | (worker_id << worker_id_shift) | sequence)
+    }
+
+    /// Compose an ID from its parts as a signed integer, for stores such as
+    /// PostgreSQL `bigint`, Java `long` and protobuf `int64`.
+    /// # Errors
+    /// Returns the errors of [`compose`](Self::compose), and
+    /// [`InvalidTotalBits`](SnowflakeError::InvalidTotalBits) if the layout
+    /// uses the sign bit, so that some of its IDs would be negative.
+    /// ```
+    /// use std::time::Duration;
+    /// use twitter_snowflake::{Layout, SnowflakeError};
+    /// let layout = Layout::new(Duration::from_millis(1), 42, 10, 12, 1704038400000);
+    /// assert_eq!(
+    ///     layout.compose_i64(1, 2, 3),
+    ///     Err(SnowflakeError::InvalidTotalBits { bits: 64, allowed: 3..=63 })
+    /// );
+    /// ```
+    pub const fn compose_i64(&self, timestamp: u64, worker_id: u64, sequence: u64) -> Result<i64, SnowflakeError> {
+        let total_bits = self.timestamp_bits + self.worker_id_bits + self.sequence_bits;
+        if total_bits > 63 {
+            return Err(SnowflakeError::InvalidTotalBits {
+                bits: total_bits,
+                allowed: 3 * MIN_FIELD_BITS..=63,
+            });
+        }
+        match self.compose(timestamp, worker_id, sequence) {
+            Ok(id) => Ok(id as i64),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the smallest ID any worker can generate at the given time.
+    /// # Examples
+    /// ```
+    /// use std::time::SystemTime;
+    /// use twitter_snowflake::Snowflake;
+    /// let mut snowflake = Snowflake::new(1).unwrap();
+    /// let id = snowflake.generate().unwrap();
+    /// assert!(snowflake.layout().min_id_at(SystemTime::now()).unwrap() <= id);
+    /// ```
+    /// # Errors
+    /// Returns an error if the time is before the epoch or after the timestamp
+    /// bits run out.
+    pub fn min_id_at(&self, time: SystemTime) -> Result<u64, SnowflakeError> {
+        self.validate()?;
+        self.compose(self.timestamp_at(time)?, 0, 0)
+    }
+
+    /// Get the largest ID any worker can generate at the given time.
+    /// # Errors
+    /// Returns an error if the time is before the epoch or after the timestamp
+    /// bits run out.
+    pub fn max_id_at(&self, time: SystemTime) -> Result<u64, SnowflakeError> {
+        self.validate()?;
+        let max_worker_id = (1u64 << self.worker_id_bits) - 1;
+        let max_sequence = (1u64 << self.sequence_bits) - 1;
+        self.compose(self.timestamp_at(time)?, max_worker_id, max_sequence)
+    }
+
+    /// Get the range of IDs any worker can generate from `start` to `end`,
+    /// both inclusive, for `WHERE id BETWEEN` queries. The range is empty if
+    /// `start` is after `end`.
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use twitter_snowflake::Snowflake;
+    /// let start = SystemTime::now() - Duration::from_secs(1);
+    /// let mut snowflake = Snowflake::new(1).unwrap();
+    /// let id = snowflake.generate().unwrap();
+    /// let end = SystemTime::now() + Duration::from_secs(1);
+    /// assert!(snowflake.layout().id_range(start, end).unwrap().contains(&id));
+    /// ```
+    /// # Errors
+    /// Returns an error if either time is before the epoch or after the
+    /// timestamp bits run out.
+    pub fn id_range(&self, start: SystemTime, end: SystemTime) -> Result<RangeInclusive<u64>, SnowflakeError> {
+        Ok(self.min_id_at(start)?..=self.max_id_at(end)?)
+    }
+
+    /// Check that the layout describes a valid 64-bit ID.
+    const fn validate(&self) -> Result<(), SnowflakeError> {
+        if self.tick.is_zero() {
+            return Err(SnowflakeError::InvalidTick { tick: self.tick });
+        }
+        if self.timestamp_bits < MIN_FIELD_BITS || self.timestamp_bits > MAX_FIELD_BITS {
+            return Err(SnowflakeError::InvalidTimestampBits {
+                bits: self.timestamp_bits,
+                allowed: MIN_FIELD_BITS..=MAX_FIELD_BITS,
+            });
+        }
+        if self.worker_id_bits < MIN_FIELD_BITS || self.worker_id_bits > MAX_FIELD_BITS {
+            return Err(SnowflakeError::InvalidWorkerIdBits {
+                bits: self.worker_id_bits,
+                allowed: MIN_FIELD_BITS..=MAX_FIELD_BITS,
+            });
+        }
+        if self.sequence_bits < MIN_FIELD_BITS || self.sequence_bits > MAX_FIELD_BITS {
+            return Err(SnowflakeError::InvalidSequenceBits {
+                bits: self.sequence_bits,
+                allowed: MIN_FIELD_BITS..=MAX_FIELD_BITS,
+            });
+        }
+        let total_bits = self.timestamp_bits + self.worker_id_bits + self.sequence_bits;
+        if total_bits > 64 {
+            return Err(SnowflakeError::InvalidTotalBits {
+                bits: total_bits,
+                allowed: 3 * MIN_FIELD_BITS..=64,
+            });
+        }
+        Ok(())
+    }
+
+    /// Get the number of ticks from the epoch to the given time.
+    pub(crate) fn timestamp_at(&self, time: SystemTime) -> Result<u64, SnowflakeError> {
+        let ticks = time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| SnowflakeError::TimestampBeforeEpoch { timestamp: time })?
+            .as_nanos()
+            / self.tick.as_nanos();
+        let ticks = u64::try_from(ticks).map_err(|_| SnowflakeError::EpochExhausted)?;
+        ticks
+            .checked_sub(self.epoch)
+            .ok_or(SnowflakeError::TimestampBeforeEpoch { timestamp: time })
+    }
+
+    /// Get the time the given number of ticks after the epoch, or `None` if
+    /// [`SystemTime`] cannot represent it.
+    pub(crate) fn time_at(&self, timestamp: u64) -> Option<SystemTime> {
+        UNIX_EPOCH
+            .checked_add(self.ticks(self.epoch))?
+            .checked_add(self.ticks(timestamp))
+    }
+
+    /// Get the duration from the Unix epoch to the given number of ticks after
+    /// the epoch, saturating on overflow.
+    pub(crate) fn since_unix_epoch(&self, timestamp: u64) -> Duration {
+        self.ticks(self.epoch).saturating_add(self.ticks(timestamp))
+    }
+
+    /// Convert a number of ticks into a duration, saturating on overflow.
+    fn ticks(&self, ticks: u64) -> Duration {
+        crate::sequencer::duration(self.tick, ticks)
+    }
+}
+
+impl Default for Layout {
+    /// The layout used by [`Snowflake::new`](crate::Snowflake::new).
+    fn default() -> Self {
+        #[cfg(feature = "float-safe")]
+        let (tick, epoch) = (Duration::from_secs(1), crate::EPOCH_SECS);
+        #[cfg(not(feature = "float-safe"))]
+        let (tick, epoch) = (Duration::from_millis(1), crate::EPOCH_MILLIS);
+        Self::new(
+            tick,
+            TIMESTAMP_BITS,
+            WORKER_ID_BITS,
+            MAX_ADJUSTABLE_BITS - WORKER_ID_BITS,
+            epoch,
+        )
+    }
+}
+
+impl fmt::Display for CapacityWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NarrowSequence { max_ids_per_tick } => {
+                write!(
+                    f,
+                    "only {max_ids_per_tick} ids fit in one tick, bursts will wait for the next tick"
+                )
+            }
+            Self::LowThroughput { max_ids_per_second } => {
+                write!(f, "a worker can only generate {max_ids_per_second} ids per second")
+            }
+            Self::ShortLifetime { lifetime } => {
+                write!(f, "the timestamp bits run out {}s after the epoch", lifetime.as_secs())
+            }
+            Self::EpochInFuture => write!(f, "the epoch lies in the future"),
+            Self::Exhausted => write!(f, "the timestamp bits have already run out"),
+            Self::SignBitUsed => write!(f, "ids use the sign bit and are negative as i64"),
+        }
+    }
+}
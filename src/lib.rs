@@ -43,20 +43,72 @@
 //!
 //! # Errors
 //!
-//! The Snowflake generator can return the following errors:
+//! The Snowflake generator can return the following errors. The list may
+//! grow in minor releases, so matches on [`SnowflakeError`] need a wildcard
+//! arm.
 //!
-//! - [`ArgumentError`](SnowflakeError::ArgumentError): Indicates an invalid
-//!   argument was provided to the Snowflake generator.
+//! - [`InvalidWorkerId`](SnowflakeError::InvalidWorkerId): Indicates that the
+//!   worker ID does not fit in the worker ID bits.
+//! - [`InvalidWorkerIdBits`](SnowflakeError::InvalidWorkerIdBits),
+//!   [`InvalidTimestampBits`](SnowflakeError::InvalidTimestampBits),
+//!   [`InvalidSequenceBits`](SnowflakeError::InvalidSequenceBits),
+//!   [`InvalidTotalBits`](SnowflakeError::InvalidTotalBits) and
+//!   [`InvalidRandomBits`](SnowflakeError::InvalidRandomBits): Indicate an
+//!   invalid bit allocation.
+//! - [`InvalidTimestamp`](SnowflakeError::InvalidTimestamp) and
+//!   [`InvalidSequence`](SnowflakeError::InvalidSequence): Indicate that a
+//!   part passed to [`Layout::compose`] does not fit in its bits.
+//! - [`InvalidTick`](SnowflakeError::InvalidTick): Indicates that a layout has
+//!   a zero tick.
 //! - [`ClockMoveBackwards`](SnowflakeError::ClockMoveBackwards): Indicates that
-//!   the system clock has moved backwards.
+//!   the system clock has moved backwards, and by how much.
 //! - [`WaitForNextPeriodTimeout`](SnowflakeError::WaitForNextPeriodTimeout):
 //!   Indicates that the generator has timed out while waiting for the next time
-//!   period.
+//!   period, and how long it waited.
+//! - [`WouldBlock`](SnowflakeError::WouldBlock): Indicates that
+//!   [`try_generate`](Snowflake::try_generate) would have to wait for the next
+//!   time period, and when that period starts.
 //! - [`InvalidEpoch`](SnowflakeError::InvalidEpoch): Indicates that the epoch
-//!   time must be greater than the current time.
+//!   time must be earlier than the current time.
 //! - [`FailedConvertToMillis`](SnowflakeError::FailedConvertToMillis):
 //!   Indicates that the generator failed to convert the timestamp to
 //!   milliseconds.
+//! - [`ImplausibleEpoch`](SnowflakeError::ImplausibleEpoch): Indicates that
+//!   the epoch time predates 2000-01-01, so it was likely computed in the
+//!   wrong unit.
+//! - [`EpochExhausted`](SnowflakeError::EpochExhausted): Indicates that the
+//!   time elapsed since the epoch no longer fits in the timestamp bits.
+//! - [`TimestampBeforeEpoch`](SnowflakeError::TimestampBeforeEpoch) and
+//!   [`TimestampInFuture`](SnowflakeError::TimestampInFuture): Indicate that a
+//!   [`Backfill`] timestamp is out of range.
+//! - [`SequenceExhausted`](SnowflakeError::SequenceExhausted): Indicates that
+//!   a [`Backfill`] has used up the sequence of a time period.
+//! - [`InvalidCharacter`](SnowflakeError::InvalidCharacter),
+//!   [`InvalidLength`](SnowflakeError::InvalidLength) and
+//!   [`IdOverflow`](SnowflakeError::IdOverflow): Indicate that a string is not
+//!   a valid [`Encoding`] of an ID.
+//! - [`ChecksumMismatch`](SnowflakeError::ChecksumMismatch): Indicates that
+//!   the check character of a string does not match, as with a mistyped ID.
+//! - [`PrefixMismatch`](SnowflakeError::PrefixMismatch): Indicates that a
+//!   string is missing the prefix of the [`Entity`] of a typed ID.
+//! - [`NegativeId`](SnowflakeError::NegativeId): Indicates that an ID is
+//!   negative as a signed 64-bit integer.
+//! - [`NoEmbeddedId`](SnowflakeError::NoEmbeddedId): Indicates that a UUID
+//!   or ULID cannot embed a Snowflake ID.
+//! - [`MissingKeyVersion`](SnowflakeError::MissingKeyVersion) and
+//!   [`UnknownKeyVersion`](SnowflakeError::UnknownKeyVersion): Indicate that
+//!   an [`Obfuscator`] has no key for a public ID.
+//! - [`PoolExhausted`](SnowflakeError::PoolExhausted): Indicates that every
+//!   worker ID of a [`SnowflakePool`] is leased by another thread.
+//! - [`NoPoolInstalled`](SnowflakeError::NoPoolInstalled): Indicates that
+//!   [`next_id`] was called before a pool was installed.
+//! - [`EmptyPool`](SnowflakeError::EmptyPool): Indicates that a
+//!   [`SnowflakePool`] was given an empty block of worker IDs.
+//! - [`MissingSetting`](SnowflakeError::MissingSetting): Indicates that a
+//!   [`CompactSnowflake`] was built without a setting that has no default.
+//! - [`InvalidShards`](SnowflakeError::InvalidShards): Indicates that the
+//!   number of [`SnowflakeShard`]s is not a power of two that fits in the
+//!   sequence bits.
 //!
 //!
 //! # Safety
@@ -64,14 +116,49 @@
 //! The Snowflake generator is safe to use in a multi-threaded environment as
 //! long as each thread has its own instance of the generator.
 
-use std::{
-    cmp::Ordering,
-    hint::spin_loop,
-    time::{Instant, SystemTime, UNIX_EPOCH},
+use {
+    sequencer::{Sequencer, Wait},
+    std::{
+        ops::RangeInclusive,
+        sync::{atomic::AtomicU64, Arc},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+mod backfill;
+mod clock;
+mod compact;
+mod const_snowflake;
+mod encoding;
+mod id;
+mod interop;
+mod layout;
+mod obfuscation;
+mod pool;
+mod random;
+mod sequencer;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod shard;
+mod snowflake128;
+
+pub use {
+    backfill::Backfill,
+    clock::CoarseClock,
+    compact::{CompactSnowflake, CompactSnowflakeBuilder, CompactWidth},
+    const_snowflake::ConstSnowflake,
+    encoding::Encoding,
+    id::{Entity, SnowflakeId},
+    layout::{CapacityPlan, CapacityWarning, Layout},
+    obfuscation::Obfuscator,
+    pool::{next_id, SnowflakePool},
+    sequencer::SequencePolicy,
+    shard::SnowflakeShard,
+    snowflake128::{Layout128, Snowflake128, Snowflake128Builder, SnowflakeId128},
 };
 
 const MIN_BITS: u64 = 1;
-const TIMEOUT_MILLIS: u128 = 1000;
+const TIMEOUT: Duration = Duration::from_millis(1000);
 
 #[cfg(feature = "float-safe")]
 const WORKER_ID_BITS: u64 = 4;
@@ -83,11 +170,16 @@ const EPOCH_MILLIS: u64 = 1704038400000; // 2024-01-01 00:00:00.000
 #[cfg(feature = "float-safe")]
 const EPOCH_SECS: u64 = 1704038400; // 2024-01-01 00:00:00
 
+// Earlier epochs most likely use the wrong tick unit
+const MIN_EPOCH: Duration = Duration::from_secs(946684800); // 2000-01-01 00:00:00
+
 #[cfg(feature = "float-safe")]
 const TIMESTAMP_BITS: u64 = 32;
 #[cfg(not(feature = "float-safe"))]
 const TIMESTAMP_BITS: u64 = 41;
 
+const MAX_TIMESTAMP: u64 = (1u64 << TIMESTAMP_BITS) - 1;
+
 #[cfg(feature = "float-safe")]
 const SAFE_UNUSED_BITS: u64 = 11;
 #[cfg(not(feature = "float-safe"))]
@@ -100,29 +192,95 @@ const MAX_ADJUSTABLE_BITS: u64 = 64 - SAFE_UNUSED_BITS - TIMESTAMP_BITS;
 
 #[derive(Debug)]
 pub struct Snowflake {
-    epoch: u64,                   // The epoch time used as a reference
-    last_timestamp: u64,          // The most recent generation time
-    worker_id: u64,               // The ID of the worker
-    sequence: u64,                // The sequence within a time period
-    timeout_millis: Option<u128>, // The timeout duration for waiting for the next time period
+    sequencer: Sequencer,      // The clock and sequence
+    worker_id: u64,            // The ID of the worker
+    timeout: Option<Duration>, // The timeout duration for waiting for the next time period
 
-    max_sequence: u64,    // The maximum sequence value
     timestamp_shift: u64, // The number of bits to shift the timestamp value
     worker_id_shift: u64, // The number of bits to shift the worker ID value
 }
 
 #[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SnowflakeError {
-    #[error("argument error: {0}")]
-    ArgumentError(String),
-    #[error("clock move backwards")]
-    ClockMoveBackwards,
-    #[error("wait for next period timeout")]
-    WaitForNextPeriodTimeout,
-    #[error("epoch must be greater than the current time")]
-    InvalidEpoch,
+    #[error("invalid worker id(={worker_id}), expected worker id ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidWorkerId {
+        worker_id: u64,
+        allowed: RangeInclusive<u64>,
+    },
+    #[error("invalid worker id bits(={bits}), expected worker id bits ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidWorkerIdBits { bits: u64, allowed: RangeInclusive<u64> },
+    #[error("invalid timestamp bits(={bits}), expected timestamp bits ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidTimestampBits { bits: u64, allowed: RangeInclusive<u64> },
+    #[error("invalid sequence bits(={bits}), expected sequence bits ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidSequenceBits { bits: u64, allowed: RangeInclusive<u64> },
+    #[error("invalid total bits(={bits}), expected total bits ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidTotalBits { bits: u64, allowed: RangeInclusive<u64> },
+    #[error("invalid random bits(={bits}), expected random bits ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidRandomBits { bits: u64, allowed: RangeInclusive<u64> },
+    #[error("invalid timestamp(={timestamp}), expected timestamp ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidTimestamp {
+        timestamp: u64,
+        allowed: RangeInclusive<u64>,
+    },
+    #[error("invalid sequence(={sequence}), expected sequence ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidSequence {
+        sequence: u64,
+        allowed: RangeInclusive<u64>,
+    },
+    #[error("invalid tick(={tick:?}), expected a non-zero tick")]
+    InvalidTick { tick: Duration },
+    #[error("clock move backwards by {drift:?}")]
+    ClockMoveBackwards { drift: Duration },
+    #[error("wait for next period timeout after {elapsed:?}")]
+    WaitForNextPeriodTimeout { elapsed: Duration },
+    #[error("would block until the next period")]
+    WouldBlock { available_at: Instant },
+    #[error("invalid epoch(={epoch}), expected an epoch earlier than the current time")]
+    InvalidEpoch { epoch: u64 },
     #[error("failed to convert timestamp to milliseconds")]
     FailedConvertToMillis,
+    #[error("epoch {epoch:?} is implausibly early, expected no earlier than {earliest:?}")]
+    ImplausibleEpoch { epoch: SystemTime, earliest: SystemTime },
+    #[error("epoch exhausted, the timestamp no longer fits in its bits")]
+    EpochExhausted,
+    #[error("timestamp {timestamp:?} is before the epoch")]
+    TimestampBeforeEpoch { timestamp: SystemTime },
+    #[error("timestamp {timestamp:?} is in the future")]
+    TimestampInFuture { timestamp: SystemTime },
+    #[error("sequence exhausted for timestamp {timestamp:?}")]
+    SequenceExhausted { timestamp: SystemTime },
+    #[error("invalid character {character:?} at position {position}")]
+    InvalidCharacter { character: char, position: usize },
+    #[error("invalid length(={length}), expected length ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidLength {
+        length: usize,
+        allowed: RangeInclusive<usize>,
+    },
+    #[error("id overflows 64 bits")]
+    IdOverflow,
+    #[error("mismatched prefix, expected {expected:?}")]
+    PrefixMismatch { expected: &'static str },
+    #[error("checksum mismatch, the id is mistyped")]
+    ChecksumMismatch,
+    #[error("negative id(={id}), expected a non-negative id")]
+    NegativeId { id: i64 },
+    #[error("no snowflake id embedded")]
+    NoEmbeddedId,
+    #[error("missing key version, expected a public id of the form <version>.<id>")]
+    MissingKeyVersion,
+    #[error("unknown key version(={version})")]
+    UnknownKeyVersion { version: u8 },
+    #[error("worker id pool exhausted, every worker id ∈ [{},{}] is in use", worker_ids.start(), worker_ids.end())]
+    PoolExhausted { worker_ids: RangeInclusive<u64> },
+    #[error("no snowflake pool installed")]
+    NoPoolInstalled,
+    #[error("empty worker id pool(={}..={}), expected at least one worker id", worker_ids.start(), worker_ids.end())]
+    EmptyPool { worker_ids: RangeInclusive<u64> },
+    #[error("missing {setting}, which has no default")]
+    MissingSetting { setting: &'static str },
+    #[error("invalid shards(={shards}), expected a power of two ∈ [{},{}]", allowed.start(), allowed.end())]
+    InvalidShards { shards: u64, allowed: RangeInclusive<u64> },
 }
 
 impl Snowflake {
@@ -155,12 +313,15 @@ impl Snowflake {
     pub fn builder() -> SnowflakeBuilder {
         SnowflakeBuilder {
             worker_id: 0,
-            worker_id_bits: Some(WORKER_ID_BITS),
-            timeout_millis: Some(TIMEOUT_MILLIS),
+            worker_id_bits: None,
+            timeout: Some(TIMEOUT),
             #[cfg(feature = "float-safe")]
-            epoch: Some(EPOCH_SECS),
+            epoch: Some(Epoch::Ticks(EPOCH_SECS)),
             #[cfg(not(feature = "float-safe"))]
-            epoch: Some(EPOCH_MILLIS),
+            epoch: Some(Epoch::Ticks(EPOCH_MILLIS)),
+            clock: None,
+            relaxed_ordering: false,
+            sequence_policy: SequencePolicy::ResetToZero,
         }
     }
 
@@ -169,23 +330,23 @@ impl Snowflake {
     /// - `worker_id`: The ID of the worker.
     /// - `worker_id_bits`: The number of bits used for the worker ID. The
     ///   default value is 10 bits.
-    /// - `timeout_millis`: The timeout duration for waiting for the next time
-    ///   period. The default value is 1000 milliseconds.
+    /// - `timeout`: The timeout duration for waiting for the next time period,
+    ///   or `None` to wait indefinitely.
     /// - `epoch`: The epoch time used as a reference. The default value is
     ///   1704038400000 (2024-01-01 00:00:00.000).
     fn with_config(
         worker_id: u64,
         worker_id_bits: Option<u64>,
-        timeout_millis: Option<u128>,
-        epoch: Option<u64>,
+        timeout: Option<Duration>,
+        epoch: Option<Epoch>,
     ) -> Result<Self, SnowflakeError> {
         let worker_id_bits = worker_id_bits.unwrap_or(WORKER_ID_BITS);
-        if !(MIN_BITS..MAX_ADJUSTABLE_BITS).contains(&worker_id_bits) {
-            return  Err(SnowflakeError::ArgumentError(
-                format!(
-                    "invalid worker id bits(={worker_id_bits}), expected worker id bits ∈ [{MIN_BITS},{MAX_ADJUSTABLE_BITS})"
-                ))
-            );
+        let allowed = MIN_BITS..=MAX_ADJUSTABLE_BITS - MIN_BITS;
+        if !allowed.contains(&worker_id_bits) {
+            return Err(SnowflakeError::InvalidWorkerIdBits {
+                bits: worker_id_bits,
+                allowed,
+            });
         }
 
         let sequence_bits = MAX_ADJUSTABLE_BITS - worker_id_bits;
@@ -195,33 +356,22 @@ impl Snowflake {
         let timestamp_shift = worker_id_bits + sequence_bits;
 
         if worker_id > max_worker_id {
-            return Err(SnowflakeError::ArgumentError(format!(
-                "invalid worker id(={worker_id}), expected worker id ∈ [0,{max_worker_id}]",
-            )));
+            return Err(SnowflakeError::InvalidWorkerId {
+                worker_id,
+                allowed: 0..=max_worker_id,
+            });
         }
 
         #[cfg(feature = "float-safe")]
-        let epoch = epoch.unwrap_or(EPOCH_SECS);
+        let epoch = epoch.unwrap_or(Epoch::Ticks(EPOCH_SECS));
         #[cfg(not(feature = "float-safe"))]
-        let epoch = epoch.unwrap_or(EPOCH_MILLIS);
-
-        #[cfg(feature = "float-safe")]
-        if epoch >= Self::timestamp()? {
-            return Err(SnowflakeError::InvalidEpoch);
-        }
-
-        #[cfg(not(feature = "float-safe"))]
-        if epoch >= Self::timestamp_millis()? {
-            return Err(SnowflakeError::InvalidEpoch);
-        }
+        let epoch = epoch.unwrap_or(Epoch::Ticks(EPOCH_MILLIS));
+        let epoch = epoch.resolve(Self::ticks(1))?;
 
         Ok(Self {
-            epoch,
-            last_timestamp: 0,
+            sequencer: Sequencer::new(Self::ticks(1), epoch, MAX_TIMESTAMP, max_sequence),
             worker_id,
-            sequence: 0,
-            timeout_millis,
-            max_sequence,
+            timeout,
             timestamp_shift,
             worker_id_shift,
         })
@@ -237,102 +387,176 @@ impl Snowflake {
     /// println!("Generated ID: {}", id);
     /// ```
     pub fn generate(&mut self) -> Result<u64, SnowflakeError> {
-        #[cfg(feature = "float-safe")]
-        let mut now = self.current_timestamp_since_epoch()?;
-        #[cfg(not(feature = "float-safe"))]
-        let mut now = self.current_timestamp_millis_since_epoch()?;
-        match now.cmp(&self.last_timestamp) {
-            // The clock has moved backwards
-            Ordering::Less => {
-                let possible_sequence = (self.sequence + 1) & self.max_sequence;
-                if possible_sequence > 0 {
-                    // Continue to use the remaining sequence in the last time period
-                    self.sequence = possible_sequence;
-                    return Ok((self.last_timestamp << self.timestamp_shift)
-                        | (self.worker_id << self.worker_id_shift)
-                        | (self.sequence));
-                }
-                // The sequence of the last period has been used up, throw an error
-                return Err(SnowflakeError::ClockMoveBackwards);
-            }
-            // Same time period, increase the sequence
-            Ordering::Equal => {
-                self.sequence = (self.sequence + 1) & self.max_sequence;
-                if self.sequence == 0 {
-                    // The sequence of the current period has been used up, waiting for the next
-                    // period
-                    let timeout_start = Instant::now();
-                    while now <= self.last_timestamp {
-                        if let Some(timeout_millis) = self.timeout_millis {
-                            if Instant::now().duration_since(timeout_start).as_millis() > timeout_millis {
-                                return Err(SnowflakeError::WaitForNextPeriodTimeout);
-                            }
-                        }
-                        #[cfg(feature = "float-safe")]
-                        if let Ok(latest_timestamp) = self.current_timestamp_since_epoch() {
-                            now = latest_timestamp;
-                        }
-                        #[cfg(not(feature = "float-safe"))]
-                        if let Ok(latest_timestamp_millis) = self.current_timestamp_millis_since_epoch() {
-                            now = latest_timestamp_millis;
-                        }
-                        spin_loop();
-                    }
-                }
-            }
-            // New time period, reset the sequence
-            Ordering::Greater => {
-                self.sequence = 0;
-            }
-        }
-        // Update the most recent generation time
-        self.last_timestamp = now;
-        Ok((now << self.timestamp_shift) | (self.worker_id << self.worker_id_shift) | (self.sequence))
+        self.next_id(Wait::Timeout(self.timeout))
     }
 
-    #[cfg(feature = "float-safe")]
-    fn timestamp() -> Result<u64, SnowflakeError> {
-        Ok(SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|_| SnowflakeError::ClockMoveBackwards)?
-            .as_secs())
+    /// Generate a new Snowflake ID as a signed integer, for stores such as
+    /// PostgreSQL `bigint`, Java `long` and protobuf `int64`. The generator
+    /// always leaves the sign bit clear, so the ID is never negative.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::{Snowflake, SnowflakeId};
+    /// let mut snowflake = Snowflake::new(1).unwrap();
+    /// let id = snowflake.generate_i64().unwrap();
+    /// assert!(id > 0);
+    /// assert_eq!(i64::try_from(SnowflakeId::try_from(id).unwrap()), Ok(id));
+    /// ```
+    pub fn generate_i64(&mut self) -> Result<i64, SnowflakeError> {
+        // The sign bit is never part of the layout
+        self.generate().map(|id| id as i64)
     }
 
-    #[cfg(not(feature = "float-safe"))]
-    fn timestamp_millis() -> Result<u64, SnowflakeError> {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|_| SnowflakeError::ClockMoveBackwards)?
-            .as_millis()
-            .try_into()
-            .map_err(|_| SnowflakeError::FailedConvertToMillis)
+    /// Generate a new Snowflake ID without waiting.
+    ///
+    /// Once the sequence of the current time period is used up,
+    /// [`generate`](Snowflake::generate) spins until the next period starts.
+    /// This returns [`WouldBlock`](SnowflakeError::WouldBlock) instead, carrying
+    /// the instant at which the next ID becomes available, so the caller can
+    /// shed load or route to another generator.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::{Snowflake, SnowflakeError};
+    /// let mut snowflake = Snowflake::new(1).unwrap();
+    /// match snowflake.try_generate() {
+    ///     Ok(id) => println!("Generated ID: {}", id),
+    ///     Err(SnowflakeError::WouldBlock { available_at }) => println!("Retry at {:?}", available_at),
+    ///     Err(e) => panic!("{}", e),
+    /// }
+    /// ```
+    pub fn try_generate(&mut self) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Never)
     }
 
-    #[cfg(feature = "float-safe")]
-    fn current_timestamp_since_epoch(&self) -> Result<u64, SnowflakeError> {
-        let now = Self::timestamp()?;
-        match now.cmp(&self.epoch) {
-            Ordering::Less => Err(SnowflakeError::ClockMoveBackwards),
-            _ => Ok(now - self.epoch),
-        }
+    /// Generate a new Snowflake ID, waiting for the next time period no later
+    /// than the given deadline.
+    ///
+    /// The deadline replaces the timeout configured on the builder for this
+    /// call, so each request can bound the wait by its own remaining budget.
+    /// An ID is still returned after the deadline if no wait is needed.
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use twitter_snowflake::Snowflake;
+    /// let mut snowflake = Snowflake::new(1).unwrap();
+    /// let deadline = Instant::now() + Duration::from_millis(5);
+    /// let id = snowflake.generate_before(deadline).unwrap();
+    /// println!("Generated ID: {}", id);
+    /// ```
+    /// # Errors
+    /// Returns [`WaitForNextPeriodTimeout`](SnowflakeError::WaitForNextPeriodTimeout)
+    /// if the deadline passes while waiting for the next time period.
+    pub fn generate_before(&mut self, deadline: Instant) -> Result<u64, SnowflakeError> {
+        self.next_id(Wait::Deadline(deadline))
     }
 
-    #[cfg(not(feature = "float-safe"))]
-    fn current_timestamp_millis_since_epoch(&self) -> Result<u64, SnowflakeError> {
-        let now = Self::timestamp_millis()?;
-        match now.cmp(&self.epoch) {
-            Ordering::Less => Err(SnowflakeError::ClockMoveBackwards),
-            _ => Ok(now - self.epoch),
-        }
+    fn next_id(&mut self, wait: Wait) -> Result<u64, SnowflakeError> {
+        let (timestamp, sequence) = self.sequencer.next(wait)?;
+        Ok((timestamp << self.timestamp_shift) | (self.worker_id << self.worker_id_shift) | sequence)
+    }
+
+    /// Get the time at which the generator runs out of timestamp bits.
+    /// From this point on, [`generate`](Snowflake::generate) returns
+    /// [`EpochExhausted`](SnowflakeError::EpochExhausted).
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use twitter_snowflake::Snowflake;
+    /// let snowflake = Snowflake::new(1).unwrap();
+    /// // The default layout lasts until 2093
+    /// assert!(snowflake.exhaustion_time() > UNIX_EPOCH + Duration::from_secs(3_800_000_000));
+    /// ```
+    pub fn exhaustion_time(&self) -> SystemTime {
+        // The fixed timestamp bits run out within decades of an epoch that
+        // precedes the current time
+        self.sequencer
+            .exhaustion_time()
+            .expect("the timestamp bits run out within the range of SystemTime")
+    }
+
+    /// Get the time left before the generator runs out of timestamp bits.
+    /// Returns [`Duration::ZERO`] once the epoch is exhausted.
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use twitter_snowflake::Snowflake;
+    /// let snowflake = Snowflake::new(1).unwrap();
+    /// // Alarm well ahead of exhaustion
+    /// assert!(snowflake.remaining_lifetime() > Duration::from_secs(10 * 365 * 24 * 60 * 60));
+    /// ```
+    pub fn remaining_lifetime(&self) -> Duration {
+        self.sequencer.remaining_lifetime()
+    }
+
+    /// Get the layout of the IDs produced by the generator.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::Snowflake;
+    /// let snowflake = Snowflake::builder().with_worker_id_bits(4).build().unwrap();
+    /// let plan = snowflake.layout().plan().unwrap();
+    /// assert_eq!(plan.max_workers, 16);
+    /// ```
+    pub fn layout(&self) -> Layout {
+        let sequence_bits = self.worker_id_shift;
+        let worker_id_bits = self.timestamp_shift - self.worker_id_shift;
+        Layout::new(
+            Self::ticks(1),
+            TIMESTAMP_BITS,
+            worker_id_bits,
+            sequence_bits,
+            self.sequencer.epoch,
+        )
+    }
+
+    /// Convert a number of ticks into a duration.
+    fn ticks(ticks: u64) -> Duration {
+        #[cfg(feature = "float-safe")]
+        return Duration::from_secs(ticks);
+        #[cfg(not(feature = "float-safe"))]
+        return Duration::from_millis(ticks);
     }
 }
 
 /// A builder for creating a Snowflake generator with custom configuration.
+#[derive(Clone)]
 pub struct SnowflakeBuilder {
     worker_id: u64,
     worker_id_bits: Option<u64>,
-    timeout_millis: Option<u128>,
-    epoch: Option<u64>,
+    timeout: Option<Duration>,
+    epoch: Option<Epoch>,
+    clock: Option<CoarseClock>,
+    relaxed_ordering: bool,
+    sequence_policy: SequencePolicy,
+}
+
+/// An epoch as set on the builder, resolved to ticks on build.
+#[derive(Debug, Clone, Copy)]
+enum Epoch {
+    Ticks(u64),
+    Time(SystemTime),
+}
+
+impl Epoch {
+    /// Resolve the epoch into ticks since the Unix epoch, rejecting epochs that
+    /// lie in the future and epoch times that predate 2000-01-01. Raw ticks
+    /// are taken as given.
+    fn resolve(self, tick: Duration) -> Result<u64, SnowflakeError> {
+        let epoch = match self {
+            Epoch::Ticks(epoch) => epoch,
+            Epoch::Time(epoch) => {
+                let earliest = UNIX_EPOCH + MIN_EPOCH;
+                match epoch.duration_since(UNIX_EPOCH) {
+                    Ok(since_unix_epoch) if since_unix_epoch >= MIN_EPOCH => {
+                        sequencer::ticks(tick, since_unix_epoch).ok_or(SnowflakeError::FailedConvertToMillis)?
+                    }
+                    _ => return Err(SnowflakeError::ImplausibleEpoch { epoch, earliest }),
+                }
+            }
+        };
+        if epoch >= sequencer::now(tick)? {
+            return Err(SnowflakeError::InvalidEpoch { epoch });
+        }
+        Ok(epoch)
+    }
 }
 
 impl SnowflakeBuilder {
@@ -348,20 +572,216 @@ impl SnowflakeBuilder {
         self
     }
 
+    /// Set the timeout duration, in milliseconds, for waiting for the next time
+    /// period.
+    pub fn with_timeout_millis(self, timeout_millis: u128) -> Self {
+        self.with_timeout(Duration::from_millis(timeout_millis.try_into().unwrap_or(u64::MAX)))
+    }
+
     /// Set the timeout duration for waiting for the next time period.
-    pub fn with_timeout_millis(mut self, timeout_millis: u128) -> Self {
-        self.timeout_millis = Some(timeout_millis);
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use twitter_snowflake::Snowflake;
+    /// let snowflake = Snowflake::builder().with_timeout(Duration::from_millis(50)).build();
+    /// assert!(snowflake.is_ok());
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
         self
     }
 
-    /// Set the epoch time.
+    /// Wait indefinitely for the next time period.
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Set the epoch time, in milliseconds since the Unix epoch, or in
+    /// seconds with the `float-safe` feature. Prefer
+    /// [`with_epoch_time`](SnowflakeBuilder::with_epoch_time), which does not
+    /// depend on the tick unit and rejects implausibly early epochs.
     pub fn with_epoch(mut self, epoch: u64) -> Self {
-        self.epoch = Some(epoch);
+        self.epoch = Some(Epoch::Ticks(epoch));
+        self
+    }
+
+    /// Set the epoch time.
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use twitter_snowflake::Snowflake;
+    /// let epoch = UNIX_EPOCH + Duration::from_secs(1609459200); // 2021-01-01 00:00:00 UTC
+    /// let snowflake = Snowflake::builder().with_epoch_time(epoch).build();
+    /// assert!(snowflake.is_ok());
+    /// ```
+    pub fn with_epoch_time(mut self, epoch: SystemTime) -> Self {
+        self.epoch = Some(Epoch::Time(epoch));
+        self
+    }
+
+    /// Read the current time from a [`CoarseClock`] instead of the system
+    /// clock, except around tick boundaries.
+    pub fn with_clock(mut self, clock: CoarseClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Set where the sequence starts in each new time period. The default is
+    /// [`SequencePolicy::ResetToZero`].
+    pub fn with_sequence_policy(mut self, policy: SequencePolicy) -> Self {
+        self.sequence_policy = policy;
+        self
+    }
+
+    /// Let the shards built by [`build_shards`](SnowflakeBuilder::build_shards)
+    /// generate without sharing their latest timestamp, trading the order of
+    /// IDs across shards for less contention. See [`SnowflakeShard`].
+    pub fn with_relaxed_ordering(mut self) -> Self {
+        self.relaxed_ordering = true;
         self
     }
 
     /// Build the Snowflake generator with the specified configuration.
+    /// # Errors
+    /// Returns an error if the epoch time predates 2000-01-01, which usually
+    /// means it was computed in the wrong unit.
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use twitter_snowflake::{Snowflake, SnowflakeError};
+    /// let epoch = UNIX_EPOCH + Duration::from_millis(1609459200); // Seconds instead of milliseconds
+    /// let snowflake = Snowflake::builder().with_epoch_time(epoch).build();
+    /// assert!(matches!(snowflake.err(), Some(SnowflakeError::ImplausibleEpoch { .. })));
+    /// ```
     pub fn build(self) -> Result<Snowflake, SnowflakeError> {
-        Snowflake::with_config(self.worker_id, self.worker_id_bits, self.timeout_millis, self.epoch)
+        let snowflake = Snowflake::with_config(self.worker_id, self.worker_id_bits, self.timeout, self.epoch)?;
+        Ok(Snowflake {
+            sequencer: self.configure(snowflake.sequencer),
+            ..snowflake
+        })
+    }
+
+    /// Apply the clock and sequence policy of the builder to a sequencer.
+    fn configure(&self, sequencer: Sequencer) -> Sequencer {
+        sequencer
+            .with_clock(self.clock.clone())
+            .with_policy(self.sequence_policy)
+    }
+
+    /// Build a Snowflake generator whose worker ID and sequence bits are fixed
+    /// at compile time, with the worker ID, timeout and epoch of the builder.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::Snowflake;
+    /// let snowflake = Snowflake::builder().with_worker_id(3).build_const::<2, 20>();
+    /// assert!(snowflake.is_ok());
+    /// ```
+    /// # Errors
+    /// Returns the same errors as [`build`](SnowflakeBuilder::build), and an
+    /// error if the builder was given a different number of worker ID bits.
+    pub fn build_const<const WORKER_ID_BITS: u64, const SEQUENCE_BITS: u64>(
+        self,
+    ) -> Result<ConstSnowflake<WORKER_ID_BITS, SEQUENCE_BITS>, SnowflakeError> {
+        if let Some(bits) = self.worker_id_bits.filter(|&bits| bits != WORKER_ID_BITS) {
+            return Err(SnowflakeError::InvalidWorkerIdBits {
+                bits,
+                allowed: WORKER_ID_BITS..=WORKER_ID_BITS,
+            });
+        }
+        let max_worker_id = ConstSnowflake::<WORKER_ID_BITS, SEQUENCE_BITS>::MAX_WORKER_ID;
+        if self.worker_id > max_worker_id {
+            return Err(SnowflakeError::InvalidWorkerId {
+                worker_id: self.worker_id,
+                allowed: 0..=max_worker_id,
+            });
+        }
+        #[cfg(feature = "float-safe")]
+        let epoch = self.epoch.unwrap_or(Epoch::Ticks(EPOCH_SECS));
+        #[cfg(not(feature = "float-safe"))]
+        let epoch = self.epoch.unwrap_or(Epoch::Ticks(EPOCH_MILLIS));
+        let epoch = epoch.resolve(Snowflake::ticks(1))?;
+        let sequencer = Sequencer::new(
+            Snowflake::ticks(1),
+            epoch,
+            ConstSnowflake::<WORKER_ID_BITS, SEQUENCE_BITS>::MAX_TIMESTAMP,
+            ConstSnowflake::<WORKER_ID_BITS, SEQUENCE_BITS>::MAX_SEQUENCE,
+        );
+        Ok(ConstSnowflake::new(
+            self.configure(sequencer),
+            self.worker_id,
+            self.timeout,
+        ))
+    }
+
+    /// Build a pool that hands out the given block of worker IDs to threads,
+    /// each with a generator of the specified configuration. The worker ID of
+    /// the builder is ignored.
+    /// # Errors
+    /// Returns the same errors as [`build`](SnowflakeBuilder::build), an
+    /// [`EmptyPool`](SnowflakeError::EmptyPool) error if the block is empty,
+    /// and an [`InvalidWorkerId`](SnowflakeError::InvalidWorkerId) error if
+    /// it does not fit in the worker ID bits.
+    /// ```
+    /// use twitter_snowflake::{Snowflake, SnowflakeError};
+    /// let pool = Snowflake::builder().with_worker_id_bits(4).build_pool(8..=16);
+    /// assert!(matches!(pool.err(), Some(SnowflakeError::InvalidWorkerId { worker_id: 16, .. })));
+    /// ```
+    pub fn build_pool(self, worker_ids: RangeInclusive<u64>) -> Result<SnowflakePool, SnowflakeError> {
+        if worker_ids.is_empty() {
+            return Err(SnowflakeError::EmptyPool { worker_ids });
+        }
+        SnowflakePool::new(self, worker_ids)
+    }
+
+    /// Build the given number of shards, which share the worker ID by
+    /// splitting the sequence bits among them.
+    /// # Errors
+    /// Returns the same errors as [`build`](SnowflakeBuilder::build), and an
+    /// error if the number of shards is not a power of two, or leaves no
+    /// sequence bits to the shards.
+    /// ```
+    /// use twitter_snowflake::{Snowflake, SnowflakeError};
+    /// # #[cfg(not(feature = "float-safe"))]
+    /// # {
+    /// let shards = Snowflake::builder().build_shards(3);
+    /// assert_eq!(
+    ///     shards.err(),
+    ///     Some(SnowflakeError::InvalidShards { shards: 3, allowed: 1..=2048 })
+    /// );
+    /// # }
+    /// ```
+    pub fn build_shards(self, shards: u64) -> Result<Vec<SnowflakeShard>, SnowflakeError> {
+        let floor = (!self.relaxed_ordering).then(|| Arc::new(AtomicU64::new(0)));
+        let snowflake = self.clone().build()?;
+        // Every shard needs at least one bit of sequence
+        let allowed = 1..=1u64 << (snowflake.worker_id_shift - 1);
+        if !allowed.contains(&shards) || !shards.is_power_of_two() {
+            return Err(SnowflakeError::InvalidShards { shards, allowed });
+        }
+        let shard_bits = shards.trailing_zeros() as u64;
+        Ok((0..shards)
+            .map(|shard| {
+                let sequencer = Sequencer::new(
+                    snowflake.sequencer.tick,
+                    snowflake.sequencer.epoch,
+                    MAX_TIMESTAMP,
+                    snowflake.sequencer.max_sequence >> shard_bits,
+                );
+                let sequencer = self.configure(sequencer).with_floor(floor.clone());
+                SnowflakeShard::new(&snowflake, sequencer, shard, shard_bits)
+            })
+            .collect())
+    }
+
+    /// Build a backfill generator with the specified configuration.
+    /// # Examples
+    /// ```
+    /// use twitter_snowflake::Snowflake;
+    /// let backfill = Snowflake::builder().with_worker_id(0).build_backfill();
+    /// assert!(backfill.is_ok());
+    /// ```
+    pub fn build_backfill(self) -> Result<Backfill, SnowflakeError> {
+        let snowflake = self.build()?;
+        Ok(Backfill::new(snowflake.layout(), snowflake.worker_id))
     }
 }
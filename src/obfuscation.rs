@@ -0,0 +1,164 @@
+use {
+    crate::{random::mix, Encoding, SnowflakeError},
+    std::fmt,
+};
+
+const ROUNDS: usize = 8;
+const SIGN_BIT: u64 = 1 << 63;
+
+/// A keyed, reversible permutation of IDs, for exposing IDs without revealing
+/// their creation time, worker ID and sequence.
+///
+/// The permutation is an 8-round Feistel network over the 64-bit space. It
+/// preserves the sign bit, so IDs of 63-bit layouts stay non-negative as
+/// `i64`. It hides the structure of IDs from casual inspection but is no
+/// substitute for authorization, since anyone holding a public ID can use it.
+///
+/// Each key has a version, which the public string form carries so that keys
+/// can be rotated: IDs are obfuscated with the current key, and revealed with
+/// whichever key their version names.
+/// # Examples
+/// ```
+/// use twitter_snowflake::{Encoding, Obfuscator};
+/// let obfuscator = Obfuscator::new(2, 0x5eed).with_previous_key(1, 0x01d);
+/// let public_id = obfuscator.encode(1234567890, Encoding::Base62);
+/// assert!(public_id.starts_with("2."));
+/// assert_eq!(obfuscator.decode(&public_id, Encoding::Base62), Ok(1234567890));
+/// ```
+#[derive(Clone)]
+pub struct Obfuscator {
+    keys: Vec<Key>, // The current key first
+}
+
+#[derive(Clone)]
+struct Key {
+    version: u8,
+    round_keys: [u64; ROUNDS],
+}
+
+impl Obfuscator {
+    /// Create an obfuscator with the current key and its version.
+    pub fn new(version: u8, key: u128) -> Self {
+        Self {
+            keys: vec![Key::new(version, key)],
+        }
+    }
+
+    /// Add a previous key, so that public IDs of its version can still be
+    /// revealed after a rotation. A key whose version is already taken is
+    /// ignored.
+    pub fn with_previous_key(mut self, version: u8, key: u128) -> Self {
+        if self.keys.iter().all(|k| k.version != version) {
+            self.keys.push(Key::new(version, key));
+        }
+        self
+    }
+
+    /// Get the version of the current key.
+    pub fn version(&self) -> u8 {
+        self.keys[0].version
+    }
+
+    /// Obfuscate an ID with the current key.
+    pub fn obfuscate(&self, id: u64) -> u64 {
+        self.keys[0].permute(id)
+    }
+
+    /// Reveal an ID obfuscated with the current key.
+    pub fn reveal(&self, id: u64) -> u64 {
+        self.keys[0].unpermute(id)
+    }
+
+    /// Obfuscate an ID with the current key into its public string form: the
+    /// key version in decimal, a dot, and the obfuscated ID in the encoding.
+    pub fn encode(&self, id: u64, encoding: Encoding) -> String {
+        format!("{}.{}", self.version(), encoding.encode(self.obfuscate(id)))
+    }
+
+    /// Reveal an ID from its public string form, with the key of its version.
+    /// # Errors
+    /// Returns an error if the string has no key version in canonical decimal
+    /// form, if the version has no key, or if the rest is not a valid encoding
+    /// of an ID.
+    pub fn decode(&self, s: &str, encoding: Encoding) -> Result<u64, SnowflakeError> {
+        let (version, encoded) = s
+            .split_once('.')
+            .and_then(|(version, encoded)| Some((parse_version(version)?, encoded)))
+            .ok_or(SnowflakeError::MissingKeyVersion)?;
+        let key = self
+            .keys
+            .iter()
+            .find(|k| k.version == version)
+            .ok_or(SnowflakeError::UnknownKeyVersion { version })?;
+        Ok(key.unpermute(encoding.decode(encoded)?))
+    }
+}
+
+impl fmt::Debug for Obfuscator {
+    // Keys are secret, so only show their versions
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let versions: Vec<_> = self.keys.iter().map(|k| k.version).collect();
+        f.debug_struct("Obfuscator").field("versions", &versions).finish()
+    }
+}
+
+impl Key {
+    fn new(version: u8, key: u128) -> Self {
+        let (low, high) = (key as u64, (key >> 64) as u64);
+        let mut state = low;
+        let mut round_keys = [0; ROUNDS];
+        for round_key in &mut round_keys {
+            state = mix(state.wrapping_add(0x9e37_79b9_7f4a_7c15) ^ high);
+            *round_key = state;
+        }
+        Self { version, round_keys }
+    }
+
+    /// Permute within the half of the space of the ID, walking the cycle until
+    /// the sign bit matches, which keeps the permutation a bijection.
+    fn permute(&self, id: u64) -> u64 {
+        let mut permuted = self.feistel(id);
+        while (permuted ^ id) & SIGN_BIT != 0 {
+            permuted = self.feistel(permuted);
+        }
+        permuted
+    }
+
+    fn unpermute(&self, id: u64) -> u64 {
+        let mut unpermuted = self.feistel_inverse(id);
+        while (unpermuted ^ id) & SIGN_BIT != 0 {
+            unpermuted = self.feistel_inverse(unpermuted);
+        }
+        unpermuted
+    }
+
+    fn feistel(&self, id: u64) -> u64 {
+        let (mut left, mut right) = ((id >> 32) as u32, id as u32);
+        for &round_key in &self.round_keys {
+            (left, right) = (right, left ^ round(right, round_key));
+        }
+        (left as u64) << 32 | right as u64
+    }
+
+    fn feistel_inverse(&self, id: u64) -> u64 {
+        let (mut left, mut right) = ((id >> 32) as u32, id as u32);
+        for &round_key in self.round_keys.iter().rev() {
+            (left, right) = (right ^ round(left, round_key), left);
+        }
+        (left as u64) << 32 | right as u64
+    }
+}
+
+/// Parse a key version in the canonical decimal form that
+/// [`Obfuscator::encode`] writes, so that each ID has a single public string
+/// form: ASCII digits only, without a sign or leading zeros.
+fn parse_version(s: &str) -> Option<u8> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) || (s.starts_with('0') && s != "0") {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn round(half: u32, round_key: u64) -> u32 {
+    mix(half as u64 ^ round_key) as u32
+}
@@ -0,0 +1,179 @@
+use {
+    crate::{sequencer::Position, Layout, Snowflake, SnowflakeBuilder, SnowflakeError},
+    std::{
+        cell::RefCell,
+        fmt,
+        ops::RangeInclusive,
+        sync::{Arc, Mutex, OnceLock, PoisonError},
+    },
+};
+
+static GLOBAL: OnceLock<SnowflakePool> = OnceLock::new();
+
+thread_local! {
+    // The generators leased by the current thread, one per pool
+    static LEASES: RefCell<Vec<Lease>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A pool that hands out the worker IDs of a block to threads, each of which
+/// lazily gets its own [`Snowflake`] generator.
+///
+/// A thread leases a worker ID on its first [`generate`](Self::generate) call
+/// and returns it when it exits. A returned worker ID may go to another thread,
+/// whose generator then continues the sequence where the thread that returned
+/// it left off, so IDs stay unique across threads.
+/// # Examples
+/// ```
+/// use std::thread;
+/// use twitter_snowflake::Snowflake;
+/// let pool = Snowflake::builder().build_pool(0..=3).unwrap();
+/// let ids = thread::scope(|s| {
+///     let handles: Vec<_> = (0..4).map(|_| s.spawn(|| pool.generate().unwrap())).collect();
+///     handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+/// });
+/// assert_eq!(ids.len(), 4);
+/// ```
+#[derive(Clone)]
+pub struct SnowflakePool {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    builder: SnowflakeBuilder,
+    worker_ids: RangeInclusive<u64>,
+    layout: Layout,
+    state: Mutex<State>,
+}
+
+struct State {
+    next_worker_id: u64,            // The first worker ID never leased
+    returned: Vec<(u64, Position)>, // The returned worker IDs and the positions of their sequencers
+}
+
+/// A generator leased by a thread, which returns its worker ID to the pool on
+/// drop.
+struct Lease {
+    shared: Arc<Shared>,
+    snowflake: Snowflake,
+}
+
+impl SnowflakePool {
+    pub(crate) fn new(builder: SnowflakeBuilder, worker_ids: RangeInclusive<u64>) -> Result<Self, SnowflakeError> {
+        // Validate the configuration and the block against a throwaway generator
+        let layout = builder.clone().with_worker_id(*worker_ids.end()).build()?.layout();
+        Ok(Self {
+            shared: Arc::new(Shared {
+                builder,
+                state: Mutex::new(State {
+                    next_worker_id: *worker_ids.start(),
+                    returned: Vec::new(),
+                }),
+                worker_ids,
+                layout,
+            }),
+        })
+    }
+
+    /// Generate a new Snowflake ID with the generator of the current thread,
+    /// leasing a worker ID for it on first use.
+    /// # Errors
+    /// Returns [`PoolExhausted`](SnowflakeError::PoolExhausted) if every worker
+    /// ID of the block is leased by another thread, and otherwise the same
+    /// errors as [`Snowflake::generate`].
+    pub fn generate(&self) -> Result<u64, SnowflakeError> {
+        LEASES.with(|leases| {
+            let mut leases = leases.borrow_mut();
+            let index = match leases.iter().position(|lease| Arc::ptr_eq(&lease.shared, &self.shared)) {
+                Some(index) => index,
+                None => {
+                    leases.push(self.lease()?);
+                    leases.len() - 1
+                }
+            };
+            leases[index].snowflake.generate()
+        })
+    }
+
+    /// Lease a worker ID and build a generator for it.
+    fn lease(&self) -> Result<Lease, SnowflakeError> {
+        let shared = &self.shared;
+        let mut state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (worker_id, position) = match state.returned.pop() {
+            Some(returned) => returned,
+            None if state.next_worker_id <= *shared.worker_ids.end() => {
+                state.next_worker_id += 1;
+                (state.next_worker_id - 1, Position::default())
+            }
+            None => {
+                return Err(SnowflakeError::PoolExhausted {
+                    worker_ids: shared.worker_ids.clone(),
+                })
+            }
+        };
+        match shared.builder.clone().with_worker_id(worker_id).build() {
+            Ok(mut snowflake) => {
+                snowflake.sequencer.resume(position);
+                Ok(Lease {
+                    shared: Arc::clone(shared),
+                    snowflake,
+                })
+            }
+            Err(e) => {
+                state.returned.push((worker_id, position));
+                Err(e)
+            }
+        }
+    }
+
+    /// Get the block of worker IDs of the pool.
+    pub fn worker_ids(&self) -> RangeInclusive<u64> {
+        self.shared.worker_ids.clone()
+    }
+
+    /// Get the layout of the IDs produced by the pool.
+    pub fn layout(&self) -> Layout {
+        self.shared.layout
+    }
+
+    /// Make the pool the one used by [`next_id`].
+    /// # Errors
+    /// Returns the pool back if a pool has already been installed.
+    pub fn install(self) -> Result<(), Self> {
+        GLOBAL.set(self)
+    }
+}
+
+impl fmt::Debug for SnowflakePool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnowflakePool")
+            .field("worker_ids", &self.shared.worker_ids)
+            .field("layout", &self.shared.layout)
+            .finish()
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state
+            .returned
+            .push((self.snowflake.worker_id, self.snowflake.sequencer.position()));
+    }
+}
+
+/// Generate a new Snowflake ID with the pool installed by
+/// [`SnowflakePool::install`].
+/// # Examples
+/// ```
+/// use twitter_snowflake::Snowflake;
+/// Snowflake::builder().build_pool(0..=15).unwrap().install().unwrap();
+/// let id = twitter_snowflake::next_id().unwrap();
+/// println!("Generated ID: {}", id);
+/// ```
+/// # Errors
+/// Returns [`NoPoolInstalled`](SnowflakeError::NoPoolInstalled) if no pool
+/// has been installed, and otherwise the same errors as
+/// [`SnowflakePool::generate`].
+pub fn next_id() -> Result<u64, SnowflakeError> {
+    GLOBAL.get().ok_or(SnowflakeError::NoPoolInstalled)?.generate()
+}
@@ -0,0 +1,7 @@
+/// The finalizer of SplitMix64.
+/// Ref: https://prng.di.unimi.it/splitmix64.c
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,332 @@
+use {
+    crate::{random::mix, CoarseClock, SnowflakeError},
+    std::{
+        cmp::Ordering,
+        hash::{BuildHasher, RandomState},
+        hint::spin_loop,
+        sync::{atomic, atomic::AtomicU64, Arc},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// How long to wait for the next time period once the sequence is used up.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Wait {
+    Never,
+    Timeout(Option<Duration>),
+    Deadline(Instant),
+}
+
+/// Where the sequence starts in each new time period.
+///
+/// Whatever the policy, a time period holds as many IDs as the sequence has
+/// values: the sequence counts up from its start, wrapping around, until it
+/// would reach the start again.
+/// # Examples
+/// ```
+/// use twitter_snowflake::{SequencePolicy, Snowflake};
+/// let mut snowflake = Snowflake::builder()
+///     .with_worker_id(1)
+///     .with_sequence_policy(SequencePolicy::RandomOffset)
+///     .build()
+///     .unwrap();
+/// let id = snowflake.generate().unwrap();
+/// println!("Generated ID: {}", id);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SequencePolicy {
+    /// Start each time period at zero. At low rates nearly every ID then has
+    /// a zero sequence, which skews sharding by `id % n`.
+    #[default]
+    ResetToZero,
+    /// Carry the sequence over from the last time period, so the low bits of
+    /// successive IDs keep counting up.
+    /// # Ordering
+    /// The sequence wraps around to zero within a time period, so IDs from one
+    /// generator are no longer increasing: only their timestamps are.
+    Carry,
+    /// Start each time period at a random sequence, which makes IDs harder to
+    /// guess.
+    /// # Ordering
+    /// The sequence wraps around to zero within a time period, so IDs from one
+    /// generator are no longer increasing: only their timestamps are.
+    RandomOffset,
+}
+
+/// The most recent timestamp and sequence handed out by a sequencer, together
+/// with the sequence at which that time period started.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Position {
+    last_timestamp: u64,
+    start: u64,
+    sequence: u64,
+}
+
+/// The clock and sequence of a generator, which hands out unique pairs of
+/// timestamp and sequence whatever the width of the IDs built from them.
+#[derive(Debug)]
+pub(crate) struct Sequencer {
+    pub(crate) tick: Duration,     // The duration of a time period
+    pub(crate) epoch: u64,         // The epoch in ticks since the Unix epoch
+    pub(crate) max_timestamp: u64, // The maximum timestamp value
+    pub(crate) max_sequence: u64,  // The maximum sequence value
+    clock: Option<CoarseClock>,    // The cached clock read outside tick boundaries
+    floor: Option<Arc<AtomicU64>>, // The latest timestamp handed out by any sequencer sharing it
+    policy: SequencePolicy,        // Where the sequence starts in each time period
+    random_state: u64,             // The state of the generator of random offsets
+    last_timestamp: u64,           // The most recent generation time
+    start: u64,                    // The sequence at which the time period started
+    sequence: u64,                 // The sequence within a time period
+}
+
+impl Sequencer {
+    pub(crate) fn new(tick: Duration, epoch: u64, max_timestamp: u64, max_sequence: u64) -> Self {
+        Self {
+            tick,
+            epoch,
+            max_timestamp,
+            max_sequence,
+            clock: None,
+            floor: None,
+            policy: SequencePolicy::ResetToZero,
+            random_state: 0,
+            last_timestamp: 0,
+            start: 0,
+            sequence: 0,
+        }
+    }
+
+    /// Read the current time from a cached clock instead of the system clock,
+    /// except around tick boundaries.
+    pub(crate) fn with_clock(mut self, clock: Option<CoarseClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Never hand out a timestamp earlier than one already handed out by the
+    /// other sequencers sharing the floor.
+    pub(crate) fn with_floor(mut self, floor: Option<Arc<AtomicU64>>) -> Self {
+        self.floor = floor;
+        self
+    }
+
+    /// Start the sequence of each time period as the policy says.
+    pub(crate) fn with_policy(mut self, policy: SequencePolicy) -> Self {
+        if policy == SequencePolicy::RandomOffset {
+            self.random_state = RandomState::new().hash_one(Instant::now());
+        }
+        self.policy = policy;
+        self
+    }
+
+    /// Continue from the position of another sequencer, so that no timestamp
+    /// and sequence is handed out twice.
+    pub(crate) fn resume(&mut self, position: Position) {
+        self.last_timestamp = position.last_timestamp;
+        self.start = position.start;
+        self.sequence = position.sequence;
+    }
+
+    /// Get the position of the sequencer.
+    pub(crate) fn position(&self) -> Position {
+        Position {
+            last_timestamp: self.last_timestamp,
+            start: self.start,
+            sequence: self.sequence,
+        }
+    }
+
+    /// Get the next unique timestamp and sequence.
+    pub(crate) fn next(&mut self, wait: Wait) -> Result<(u64, u64), SnowflakeError> {
+        self.next_within(wait, self.max_timestamp, self.max_sequence)
+    }
+
+    /// Get the next unique timestamp and sequence, given the largest timestamp
+    /// and sequence of the sequencer. Always inlined, so that callers with a
+    /// compile-time layout check and mask against constants.
+    #[inline(always)]
+    pub(crate) fn next_within(
+        &mut self,
+        wait: Wait,
+        max_timestamp: u64,
+        max_sequence: u64,
+    ) -> Result<(u64, u64), SnowflakeError> {
+        let mut now = self.raise_to_floor(self.cached_timestamp_since_epoch()?);
+        if now > max_timestamp {
+            // The timestamp would overflow into the other fields
+            return Err(SnowflakeError::EpochExhausted);
+        }
+        match now.cmp(&self.last_timestamp) {
+            // The clock has moved backwards
+            Ordering::Less => {
+                let possible_sequence = (self.sequence + 1) & max_sequence;
+                if possible_sequence != self.start {
+                    // Continue to use the remaining sequence in the last time period
+                    self.sequence = possible_sequence;
+                    return Ok((self.last_timestamp, self.sequence));
+                }
+                // The sequence of the last period has been used up, throw an error
+                return Err(SnowflakeError::ClockMoveBackwards {
+                    drift: duration(self.tick, self.last_timestamp - now),
+                });
+            }
+            // Same time period, increase the sequence
+            Ordering::Equal => {
+                let sequence = (self.sequence + 1) & max_sequence;
+                if sequence == self.start {
+                    // The sequence of the current period has been used up, waiting for the next
+                    // period
+                    now = self.raise_to_floor(self.wait_for_next_period(wait)?);
+                    self.start_period(max_sequence);
+                } else {
+                    self.sequence = sequence;
+                }
+            }
+            // New time period, restart the sequence
+            Ordering::Greater => self.start_period(max_sequence),
+        }
+        // Update the most recent generation time
+        self.last_timestamp = now;
+        Ok((now, self.sequence))
+    }
+
+    /// Start the sequence of a new time period.
+    #[inline(always)]
+    fn start_period(&mut self, max_sequence: u64) {
+        self.sequence = match self.policy {
+            SequencePolicy::ResetToZero => 0,
+            SequencePolicy::Carry => (self.sequence + 1) & max_sequence,
+            SequencePolicy::RandomOffset => {
+                self.random_state = self.random_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                mix(self.random_state) & max_sequence
+            }
+        };
+        self.start = self.sequence;
+    }
+
+    /// Raise the timestamp to the floor, if any, and the floor to the
+    /// timestamp.
+    #[inline]
+    fn raise_to_floor(&self, now: u64) -> u64 {
+        match &self.floor {
+            Some(floor) => now.max(floor.fetch_max(now, atomic::Ordering::Relaxed)),
+            None => now,
+        }
+    }
+
+    /// Wait until the clock moves past the last time period and return the new
+    /// timestamp.
+    fn wait_for_next_period(&self, wait: Wait) -> Result<u64, SnowflakeError> {
+        let timeout_start = Instant::now();
+        let deadline = match wait {
+            Wait::Never => {
+                return Err(SnowflakeError::WouldBlock {
+                    available_at: self.next_period_instant(),
+                })
+            }
+            Wait::Timeout(timeout) => timeout.and_then(|timeout| timeout_start.checked_add(timeout)),
+            Wait::Deadline(deadline) => Some(deadline),
+        };
+        let mut now = self.last_timestamp;
+        while now <= self.last_timestamp {
+            if let Some(deadline) = deadline {
+                let instant = Instant::now();
+                if instant > deadline {
+                    return Err(SnowflakeError::WaitForNextPeriodTimeout {
+                        elapsed: instant.duration_since(timeout_start),
+                    });
+                }
+            }
+            if let Ok(latest_timestamp) = self.current_timestamp_since_epoch() {
+                now = latest_timestamp;
+            }
+            spin_loop();
+        }
+        if now > self.max_timestamp {
+            return Err(SnowflakeError::EpochExhausted);
+        }
+        Ok(now)
+    }
+
+    /// Get the instant at which the time period after the last one starts.
+    fn next_period_instant(&self) -> Instant {
+        let next_period = UNIX_EPOCH + duration(self.tick, self.epoch + self.last_timestamp + 1);
+        let remaining = next_period.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO);
+        Instant::now() + remaining
+    }
+
+    /// Get the time at which the timestamp runs out of bits, or `None` if
+    /// [`SystemTime`] cannot represent it.
+    pub(crate) fn exhaustion_time(&self) -> Option<SystemTime> {
+        UNIX_EPOCH
+            .checked_add(duration(self.tick, self.epoch))?
+            .checked_add(duration(self.tick, self.max_timestamp))?
+            .checked_add(self.tick)
+    }
+
+    /// Get the time left before the timestamp runs out of bits, which is
+    /// [`Duration::MAX`] if [`SystemTime`] cannot represent the exhaustion
+    /// time.
+    pub(crate) fn remaining_lifetime(&self) -> Duration {
+        self.exhaustion_time().map_or(Duration::MAX, |exhaustion_time| {
+            exhaustion_time
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+        })
+    }
+
+    /// Get the current timestamp from the cached clock, falling back to the
+    /// system clock if there is none or if it lags behind the last timestamp.
+    #[inline]
+    fn cached_timestamp_since_epoch(&self) -> Result<u64, SnowflakeError> {
+        if let Some(clock) = &self.clock {
+            let now = ticks(self.tick, clock.since_unix_epoch()).ok_or(SnowflakeError::FailedConvertToMillis)?;
+            if now >= self.epoch + self.last_timestamp {
+                return Ok(now - self.epoch);
+            }
+        }
+        self.current_timestamp_since_epoch()
+    }
+
+    fn current_timestamp_since_epoch(&self) -> Result<u64, SnowflakeError> {
+        let now = now(self.tick)?;
+        match now.cmp(&self.epoch) {
+            Ordering::Less => Err(SnowflakeError::ClockMoveBackwards {
+                drift: duration(self.tick, self.epoch - now),
+            }),
+            _ => Ok(now - self.epoch),
+        }
+    }
+}
+
+/// Get the current time in ticks since the Unix epoch.
+pub(crate) fn now(tick: Duration) -> Result<u64, SnowflakeError> {
+    let since_unix_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| SnowflakeError::ClockMoveBackwards { drift: e.duration() })?;
+    ticks(tick, since_unix_epoch).ok_or(SnowflakeError::FailedConvertToMillis)
+}
+
+/// Convert a duration into a number of whole ticks.
+pub(crate) fn ticks(tick: Duration, duration: Duration) -> Option<u64> {
+    let tick_nanos = tick.as_nanos();
+    if tick_nanos <= NANOS_PER_SEC as u128 && NANOS_PER_SEC % tick_nanos as u64 == 0 {
+        // Stay clear of 128-bit division for ticks that divide a second
+        let tick_nanos = tick_nanos as u64;
+        let ticks_per_sec = NANOS_PER_SEC / tick_nanos;
+        let ticks = duration.as_secs().checked_mul(ticks_per_sec)?;
+        return ticks.checked_add(duration.subsec_nanos() as u64 / tick_nanos);
+    }
+    (duration.as_nanos() / tick_nanos).try_into().ok()
+}
+
+/// Convert a number of ticks into a duration, saturating on overflow.
+pub(crate) fn duration(tick: Duration, ticks: u64) -> Duration {
+    let nanos = tick.as_nanos().saturating_mul(ticks as u128);
+    match u64::try_from(nanos / NANOS_PER_SEC as u128) {
+        Ok(secs) => Duration::new(secs, (nanos % NANOS_PER_SEC as u128) as u32),
+        Err(_) => Duration::MAX,
+    }
+}
@@ -0,0 +1,186 @@
+//! Serde support for Snowflake IDs, enabled by the `serde` feature.
+//!
+//! [`SnowflakeId`] serializes as a number by default.
+//! JavaScript clients lose precision above 2^53, so the modules below select
+//! another representation per field with `#[serde(with = "...")]`. They work
+//! on both `SnowflakeId` and `u64` fields, and always deserialize both the
+//! number and the string form. Formats that are not human-readable, such as
+//! bincode, are not self-describing, so they only read back the form written.
+//!
+//! IDs of an [`Entity`] with a prefix serialize as their prefixed string form
+//! instead, such as `"ord_1LY7VK"`, and deserialize only from it.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use twitter_snowflake::SnowflakeId;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct User {
+//!     #[serde(with = "twitter_snowflake::serde::as_string")]
+//!     id: SnowflakeId,
+//!     #[serde(with = "twitter_snowflake::serde::base62")]
+//!     team_id: u64,
+//! }
+//!
+//! let user = User { id: SnowflakeId::new(1234567890), team_id: 1234567890 };
+//! let json = serde_json::to_string(&user).unwrap();
+//! assert_eq!(json, r#"{"id":"1234567890","team_id":"1LY7VK"}"#);
+//! ```
+
+use {
+    crate::{Encoding, Entity, SnowflakeId},
+    ::serde::{
+        de::{self, Unexpected, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+    std::{fmt, marker::PhantomData},
+};
+
+/// Accepts an ID as a number, or as a string in the given encoding, or in
+/// decimal if none.
+struct IdVisitor(Option<Encoding>);
+
+impl Visitor<'_> for IdVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(encoding) => write!(f, "a snowflake id as a number or a {encoding:?} string"),
+            None => write!(f, "a snowflake id as a number or a decimal string"),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+        u64::try_from(v).map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+        match self.0 {
+            Some(encoding) => encoding.decode(v).map_err(E::custom),
+            None => v.parse().map_err(|_| E::invalid_value(Unexpected::Str(v), &self)),
+        }
+    }
+}
+
+/// Deserialize an ID from either form in human-readable formats, and from the
+/// form it is serialized as, a string or a number, in the others.
+fn deserialize_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    encoding: Option<Encoding>,
+    as_string: bool,
+) -> Result<u64, D::Error> {
+    let visitor = IdVisitor(encoding);
+    match (deserializer.is_human_readable(), as_string) {
+        (true, _) => deserializer.deserialize_any(visitor),
+        (false, true) => deserializer.deserialize_str(visitor),
+        (false, false) => deserializer.deserialize_u64(visitor),
+    }
+}
+
+impl<T: Entity> Serialize for SnowflakeId<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match T::PREFIX {
+            Some(_) => serializer.collect_str(self),
+            None => serializer.serialize_u64(self.get()),
+        }
+    }
+}
+
+impl<'de, T: Entity> Deserialize<'de> for SnowflakeId<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match T::PREFIX {
+            Some(_) => deserializer.deserialize_str(PrefixedIdVisitor(PhantomData)),
+            None => deserialize_id(deserializer, None, false).map(|id| SnowflakeId::new(id).cast()),
+        }
+    }
+}
+
+/// Accepts an ID of an entity with a prefix as its prefixed string form.
+struct PrefixedIdVisitor<T>(PhantomData<fn() -> T>);
+
+impl<T: Entity> Visitor<'_> for PrefixedIdVisitor<T> {
+    type Value = SnowflakeId<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a snowflake id with the prefix {:?}", T::PREFIX.unwrap_or_default())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+/// Serialize an ID as a JSON number.
+pub mod as_number {
+    use super::*;
+
+    pub fn serialize<T: Copy + Into<u64>, S: Serializer>(id: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64((*id).into())
+    }
+
+    pub fn deserialize<'de, T: From<u64>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        deserialize_id(deserializer, None, false).map(T::from)
+    }
+}
+
+/// Serialize an ID as a decimal string.
+pub mod as_string {
+    use super::*;
+
+    pub fn serialize<T: Copy + Into<u64>, S: Serializer>(id: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&(*id).into())
+    }
+
+    pub fn deserialize<'de, T: From<u64>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        deserialize_id(deserializer, None, true).map(T::from)
+    }
+}
+
+macro_rules! encoding_module {
+    ($(#[$doc:meta])* $name:ident, $encoding:expr) => {
+        $(#[$doc])*
+        pub mod $name {
+            use super::*;
+
+            pub fn serialize<T: Copy + Into<u64>, S: Serializer>(id: &T, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&$encoding.encode((*id).into()))
+            }
+
+            pub fn deserialize<'de, T: From<u64>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+                deserialize_id(deserializer, Some($encoding), true).map(T::from)
+            }
+        }
+    };
+}
+
+encoding_module!(
+    /// Serialize an ID as a Crockford base32 string.
+    crockford,
+    Encoding::Crockford
+);
+encoding_module!(
+    /// Serialize an ID as a base58 string.
+    base58,
+    Encoding::Base58
+);
+encoding_module!(
+    /// Serialize an ID as a base62 string.
+    base62,
+    Encoding::Base62
+);
+encoding_module!(
+    /// Serialize an ID as a URL-safe base64 string.
+    base64_url,
+    Encoding::Base64Url
+);
+encoding_module!(
+    /// Serialize an ID as a sortable, fixed-width string.
+    sortable,
+    Encoding::Sortable
+);
@@ -0,0 +1,365 @@
+use {
+    crate::{
+        random::mix,
+        sequencer::{self, Sequencer, Wait},
+        Epoch, SnowflakeError, TIMEOUT,
+    },
+    std::{
+        fmt,
+        hash::{BuildHasher, RandomState},
+        ops::RangeInclusive,
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+const TIMESTAMP_BITS: u64 = 64;
+const FIELD_BITS: u64 = 128 - TIMESTAMP_BITS; // Shared by worker ID, sequence and random bits
+const WORKER_ID_BITS: RangeInclusive<u64> = 16..=32;
+const DEFAULT_WORKER_ID_BITS: u64 = 16;
+const TICK: Duration = Duration::from_micros(1);
+const MAX_TICK: Duration = Duration::from_millis(1);
+const EPOCH: Duration = Duration::from_secs(1704038400); // 2024-01-01 00:00:00
+
+/// A generator of 128-bit Snowflake IDs.
+///
+/// Each ID holds a 64-bit timestamp in the upper half, and the worker ID,
+/// sequence and optional random tail in the lower half. Ticks default to one
+/// microsecond, so the timestamp alone lasts for millennia, and 16 to 32 bits
+/// of worker ID leave up to 48 bits of sequence. The clock is handled exactly
+/// as by [`Snowflake`](crate::Snowflake): the same timeout, waiting and
+/// backwards clock rules apply.
+/// # Examples
+/// ```
+/// use twitter_snowflake::{Snowflake128, SnowflakeId128};
+/// let mut snowflake = Snowflake128::builder().with_worker_id(70_000).with_worker_id_bits(20).build().unwrap();
+/// let id = SnowflakeId128::new(snowflake.generate().unwrap());
+/// assert_eq!(id.worker_id(&snowflake.layout()), 70_000);
+/// ```
+#[derive(Debug)]
+pub struct Snowflake128 {
+    sequencer: Sequencer,      // The clock and sequence
+    worker_id: u64,            // The ID of the worker
+    timeout: Option<Duration>, // The timeout duration for waiting for the next time period
+    random_state: u64,         // The state of the generator of random bits
+
+    worker_id_bits: u64, // The number of bits used for the worker ID
+    sequence_bits: u64,  // The number of bits used for the sequence
+    random_bits: u64,    // The number of random bits below the sequence
+}
+
+/// A builder for creating a 128-bit Snowflake generator with custom
+/// configuration.
+pub struct Snowflake128Builder {
+    worker_id: u64,
+    worker_id_bits: u64,
+    random_bits: u64,
+    tick: Duration,
+    timeout: Option<Duration>,
+    epoch: SystemTime,
+}
+
+/// The bit allocation and time base of a 128-bit Snowflake ID. The timestamp
+/// always takes the upper 64 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawLayout128"))]
+pub struct Layout128 {
+    /// The duration of one timestamp tick.
+    pub tick: Duration,
+    /// The number of bits used for the worker ID.
+    pub worker_id_bits: u64,
+    /// The number of bits used for the sequence.
+    pub sequence_bits: u64,
+    /// The number of random bits below the sequence.
+    pub random_bits: u64,
+    /// The epoch, in ticks since the Unix epoch.
+    pub epoch: u64,
+}
+
+/// A 128-bit layout as deserialized, before validation.
+#[cfg(feature = "serde")]
+#[derive(::serde::Deserialize)]
+struct RawLayout128 {
+    tick: Duration,
+    worker_id_bits: u64,
+    sequence_bits: u64,
+    random_bits: u64,
+    epoch: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawLayout128> for Layout128 {
+    type Error = SnowflakeError;
+
+    fn try_from(raw: RawLayout128) -> Result<Self, Self::Error> {
+        let layout = Layout128 {
+            tick: raw.tick,
+            worker_id_bits: raw.worker_id_bits,
+            sequence_bits: raw.sequence_bits,
+            random_bits: raw.random_bits,
+            epoch: raw.epoch,
+        };
+        layout.validate()?;
+        Ok(layout)
+    }
+}
+
+impl Layout128 {
+    /// Check that the layout is one a [`Snowflake128`] can produce, so that
+    /// its fields fill the lower 64 bits.
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> Result<(), SnowflakeError> {
+        if self.tick.is_zero() || self.tick > MAX_TICK {
+            return Err(SnowflakeError::InvalidTick { tick: self.tick });
+        }
+        if !WORKER_ID_BITS.contains(&self.worker_id_bits) {
+            return Err(SnowflakeError::InvalidWorkerIdBits {
+                bits: self.worker_id_bits,
+                allowed: WORKER_ID_BITS,
+            });
+        }
+        let allowed = 0..=FIELD_BITS - self.worker_id_bits - 1;
+        if !allowed.contains(&self.random_bits) {
+            return Err(SnowflakeError::InvalidRandomBits {
+                bits: self.random_bits,
+                allowed,
+            });
+        }
+        let sequence_bits = FIELD_BITS - self.worker_id_bits - self.random_bits;
+        if self.sequence_bits != sequence_bits {
+            return Err(SnowflakeError::InvalidSequenceBits {
+                bits: self.sequence_bits,
+                allowed: sequence_bits..=sequence_bits,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A 128-bit Snowflake ID.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SnowflakeId128(u128);
+
+impl Snowflake128 {
+    /// Create a new 128-bit Snowflake builder with the default configuration.
+    pub fn builder() -> Snowflake128Builder {
+        Snowflake128Builder {
+            worker_id: 0,
+            worker_id_bits: DEFAULT_WORKER_ID_BITS,
+            random_bits: 0,
+            tick: TICK,
+            timeout: Some(TIMEOUT),
+            epoch: UNIX_EPOCH + EPOCH,
+        }
+    }
+
+    /// Generate a new 128-bit Snowflake ID.
+    /// # Errors
+    /// Returns the same errors as [`Snowflake::generate`](crate::Snowflake::generate).
+    pub fn generate(&mut self) -> Result<u128, SnowflakeError> {
+        self.next_id(Wait::Timeout(self.timeout))
+    }
+
+    /// Generate a new 128-bit Snowflake ID without waiting, as
+    /// [`Snowflake::try_generate`](crate::Snowflake::try_generate) does.
+    pub fn try_generate(&mut self) -> Result<u128, SnowflakeError> {
+        self.next_id(Wait::Never)
+    }
+
+    /// Generate a new 128-bit Snowflake ID, waiting for the next time period
+    /// no later than the given deadline, as
+    /// [`Snowflake::generate_before`](crate::Snowflake::generate_before) does.
+    pub fn generate_before(&mut self, deadline: Instant) -> Result<u128, SnowflakeError> {
+        self.next_id(Wait::Deadline(deadline))
+    }
+
+    fn next_id(&mut self, wait: Wait) -> Result<u128, SnowflakeError> {
+        let (timestamp, sequence) = self.sequencer.next(wait)?;
+        let random = match self.random_bits {
+            0 => 0,
+            bits => {
+                self.random_state = self.random_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                mix(self.random_state) >> (64 - bits)
+            }
+        };
+        let fields =
+            (self.worker_id << (self.sequence_bits + self.random_bits)) | (sequence << self.random_bits) | random;
+        Ok((timestamp as u128) << FIELD_BITS | fields as u128)
+    }
+
+    /// Get the time at which the generator runs out of timestamp bits, or
+    /// `None` if [`SystemTime`] cannot represent it, as with the default
+    /// microsecond tick.
+    pub fn exhaustion_time(&self) -> Option<SystemTime> {
+        self.sequencer.exhaustion_time()
+    }
+
+    /// Get the layout of the IDs produced by the generator.
+    pub fn layout(&self) -> Layout128 {
+        Layout128 {
+            tick: self.sequencer.tick,
+            worker_id_bits: self.worker_id_bits,
+            sequence_bits: self.sequence_bits,
+            random_bits: self.random_bits,
+            epoch: self.sequencer.epoch,
+        }
+    }
+}
+
+impl Snowflake128Builder {
+    /// Set the worker ID for the generator.
+    pub fn with_worker_id(mut self, worker_id: u64) -> Self {
+        self.worker_id = worker_id;
+        self
+    }
+
+    /// Set the number of bits used for the worker ID, between 16 and 32. The
+    /// default value is 16 bits.
+    pub fn with_worker_id_bits(mut self, worker_id_bits: u64) -> Self {
+        self.worker_id_bits = worker_id_bits;
+        self
+    }
+
+    /// Set the number of random bits below the sequence, which make IDs hard
+    /// to guess. They come out of the sequence bits. The default is none.
+    pub fn with_random_bits(mut self, random_bits: u64) -> Self {
+        self.random_bits = random_bits;
+        self
+    }
+
+    /// Set the duration of one timestamp tick, at most one millisecond. The
+    /// default value is one microsecond.
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// Set the timeout duration for waiting for the next time period.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Wait indefinitely for the next time period.
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Set the epoch time.
+    pub fn with_epoch_time(mut self, epoch: SystemTime) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Build the 128-bit Snowflake generator with the specified configuration.
+    /// # Errors
+    /// Returns an error if a field does not fit, if the tick is zero or longer
+    /// than a millisecond, or if the epoch predates 2000-01-01 or is in the
+    /// future.
+    /// ```
+    /// use twitter_snowflake::{Snowflake128, SnowflakeError};
+    /// let snowflake = Snowflake128::builder().with_worker_id_bits(8).build();
+    /// assert_eq!(
+    ///     snowflake.err(),
+    ///     Some(SnowflakeError::InvalidWorkerIdBits { bits: 8, allowed: 16..=32 })
+    /// );
+    /// ```
+    pub fn build(self) -> Result<Snowflake128, SnowflakeError> {
+        if !WORKER_ID_BITS.contains(&self.worker_id_bits) {
+            return Err(SnowflakeError::InvalidWorkerIdBits {
+                bits: self.worker_id_bits,
+                allowed: WORKER_ID_BITS,
+            });
+        }
+        // Keep at least one bit for the sequence
+        let allowed = 0..=FIELD_BITS - self.worker_id_bits - 1;
+        if !allowed.contains(&self.random_bits) {
+            return Err(SnowflakeError::InvalidRandomBits {
+                bits: self.random_bits,
+                allowed,
+            });
+        }
+        let max_worker_id = (1u64 << self.worker_id_bits) - 1;
+        if self.worker_id > max_worker_id {
+            return Err(SnowflakeError::InvalidWorkerId {
+                worker_id: self.worker_id,
+                allowed: 0..=max_worker_id,
+            });
+        }
+        if self.tick.is_zero() || self.tick > MAX_TICK {
+            return Err(SnowflakeError::InvalidTick { tick: self.tick });
+        }
+
+        let epoch = Epoch::Time(self.epoch).resolve(self.tick)?;
+
+        let sequence_bits = FIELD_BITS - self.worker_id_bits - self.random_bits;
+        Ok(Snowflake128 {
+            sequencer: Sequencer::new(self.tick, epoch, u64::MAX, (1u64 << sequence_bits) - 1),
+            worker_id: self.worker_id,
+            timeout: self.timeout,
+            random_state: RandomState::new().hash_one(Instant::now()),
+            worker_id_bits: self.worker_id_bits,
+            sequence_bits,
+            random_bits: self.random_bits,
+        })
+    }
+}
+
+impl SnowflakeId128 {
+    /// Create a new 128-bit Snowflake ID from its integer value.
+    pub const fn new(id: u128) -> Self {
+        Self(id)
+    }
+
+    /// Get the integer value of the ID.
+    pub const fn get(self) -> u128 {
+        self.0
+    }
+
+    /// Get the number of ticks from the epoch to the creation of the ID.
+    pub const fn ticks(self) -> u64 {
+        (self.0 >> FIELD_BITS) as u64
+    }
+
+    /// Get the ID of the worker that generated the ID.
+    pub const fn worker_id(self, layout: &Layout128) -> u64 {
+        (self.0 as u64 >> (layout.sequence_bits + layout.random_bits)) & ((1u64 << layout.worker_id_bits) - 1)
+    }
+
+    /// Get the sequence of the ID within its time period.
+    pub const fn sequence(self, layout: &Layout128) -> u64 {
+        (self.0 as u64 >> layout.random_bits) & ((1u64 << layout.sequence_bits) - 1)
+    }
+
+    /// Get the random tail of the ID.
+    pub const fn random(self, layout: &Layout128) -> u64 {
+        self.0 as u64 & ((1u64 << layout.random_bits) - 1)
+    }
+
+    /// Get the creation time of the ID, honouring the epoch and tick of the
+    /// layout, or `None` if [`SystemTime`] cannot represent it.
+    pub fn timestamp(self, layout: &Layout128) -> Option<SystemTime> {
+        UNIX_EPOCH
+            .checked_add(sequencer::duration(layout.tick, layout.epoch))?
+            .checked_add(sequencer::duration(layout.tick, self.ticks()))
+    }
+}
+
+impl From<u128> for SnowflakeId128 {
+    fn from(id: u128) -> Self {
+        Self(id)
+    }
+}
+
+impl From<SnowflakeId128> for u128 {
+    fn from(id: SnowflakeId128) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for SnowflakeId128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
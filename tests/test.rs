@@ -1,4 +1,7 @@
-use twitter_snowflake::{Snowflake, SnowflakeError};
+use twitter_snowflake::{
+    Backfill, CapacityWarning, CoarseClock, CompactSnowflake, CompactWidth, ConstSnowflake, Encoding, Entity, Layout,
+    Layout128, Obfuscator, SequencePolicy, Snowflake, Snowflake128, SnowflakeError, SnowflakeId, SnowflakeId128,
+};
 
 #[test]
 fn test_new() {
@@ -11,7 +14,10 @@ fn test_new() {
 fn test_invalid_worker_id() {
     let worker_id = 1024;
     let snowflake = Snowflake::new(worker_id);
-    assert!(matches!(snowflake.err(), Some(SnowflakeError::ArgumentError(..))));
+    assert!(matches!(
+        snowflake.err(),
+        Some(SnowflakeError::InvalidWorkerId { worker_id: 1024, .. })
+    ));
 }
 
 #[test]
@@ -40,6 +46,290 @@ fn test_id_order() {
     assert!(sfid1 < sfid2);
 }
 
+#[test]
+fn test_try_generate() {
+    let mut snowflake = Snowflake::new(1).unwrap();
+    let sfid1 = snowflake.try_generate().unwrap();
+    let sfid2 = snowflake.try_generate().unwrap();
+    assert!(sfid1 < sfid2);
+}
+
+#[test]
+fn test_try_generate_would_block() {
+    use std::time::{Duration, Instant};
+
+    // Leave a single sequence bit, so only two IDs fit in one time period
+    let layout = Snowflake::new(0).unwrap().layout();
+    let mut snowflake = Snowflake::builder()
+        .with_worker_id_bits(layout.worker_id_bits + layout.sequence_bits - 1)
+        .build()
+        .unwrap();
+    let mut ids = Vec::new();
+    let available_at = loop {
+        match snowflake.try_generate() {
+            Ok(id) => ids.push(id),
+            Err(SnowflakeError::WouldBlock { available_at }) => break available_at,
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    };
+    assert!(available_at > Instant::now() - Duration::from_secs(1));
+    let id = loop {
+        if let Ok(id) = snowflake.try_generate() {
+            break id;
+        }
+    };
+    assert!(ids.iter().all(|&sfid| sfid < id));
+}
+
+#[test]
+fn test_generate_before_deadline() {
+    use std::time::Instant;
+
+    // Leave a single sequence bit, so only two IDs fit in one time period
+    let layout = Snowflake::new(0).unwrap().layout();
+    let mut snowflake = Snowflake::builder()
+        .with_worker_id_bits(layout.worker_id_bits + layout.sequence_bits - 1)
+        .build()
+        .unwrap();
+    // A deadline in the past still allows IDs that need no wait, but fails as
+    // soon as the sequence is used up
+    let deadline = Instant::now();
+    let err = loop {
+        if let Err(e) = snowflake.generate_before(deadline) {
+            break e;
+        }
+    };
+    assert!(matches!(err, SnowflakeError::WaitForNextPeriodTimeout { .. }));
+}
+
+#[test]
+fn test_snowflake128() {
+    use std::{
+        collections::HashSet,
+        time::{Duration, SystemTime},
+    };
+
+    let worker_id = (1 << 24) + 7;
+    let mut snowflake = Snowflake128::builder()
+        .with_worker_id(worker_id)
+        .with_worker_id_bits(32)
+        .with_random_bits(8)
+        .build()
+        .unwrap();
+    let layout = snowflake.layout();
+    assert_eq!(layout.sequence_bits, 24);
+    let start = SystemTime::now();
+    let ids: Vec<_> = (0..10_000).map(|_| snowflake.generate().unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(
+        ids.iter()
+            .map(|&id| SnowflakeId128::new(id).random(&layout))
+            .collect::<HashSet<_>>()
+            .len()
+            > 1
+    );
+
+    let sfid = SnowflakeId128::new(ids[0]);
+    assert_eq!(sfid.worker_id(&layout), worker_id);
+    assert!(sfid.random(&layout) < 1 << 8);
+    let timestamp = sfid.timestamp(&layout).unwrap();
+    assert!(timestamp + layout.tick > start);
+    assert!(timestamp < start + Duration::from_secs(1));
+    // Microsecond ticks in 64 bits last for over 500,000 years, which some
+    // platforms cannot represent as a SystemTime
+    if let Some(exhaustion_time) = snowflake.exhaustion_time() {
+        let lifetime = exhaustion_time.duration_since(SystemTime::now()).unwrap();
+        assert!(lifetime > Duration::from_secs(500_000 * 365 * 24 * 60 * 60));
+    }
+}
+
+#[test]
+fn test_snowflake128_would_block() {
+    // Leave a single sequence bit, so only two IDs fit in one time period
+    let mut snowflake = Snowflake128::builder()
+        .with_worker_id_bits(32)
+        .with_random_bits(31)
+        .with_tick(std::time::Duration::from_millis(1))
+        .build()
+        .unwrap();
+    let err = loop {
+        if let Err(e) = snowflake.try_generate() {
+            break e;
+        }
+    };
+    assert!(matches!(err, SnowflakeError::WouldBlock { .. }));
+    assert!(snowflake.generate().is_ok());
+}
+
+#[test]
+fn test_snowflake128_invalid_config() {
+    use std::time::{Duration, SystemTime};
+
+    let result = Snowflake128::builder().with_worker_id_bits(33).build();
+    assert!(matches!(
+        result,
+        Err(SnowflakeError::InvalidWorkerIdBits { bits: 33, .. })
+    ));
+    let result = Snowflake128::builder()
+        .with_worker_id_bits(20)
+        .with_random_bits(44)
+        .build();
+    assert_eq!(
+        result.err(),
+        Some(SnowflakeError::InvalidRandomBits {
+            bits: 44,
+            allowed: 0..=43
+        })
+    );
+    let result = Snowflake128::builder().with_worker_id(1 << 16).build();
+    assert!(matches!(result, Err(SnowflakeError::InvalidWorkerId { .. })));
+    let result = Snowflake128::builder().with_tick(Duration::from_millis(2)).build();
+    assert!(matches!(result, Err(SnowflakeError::InvalidTick { .. })));
+    let result = Snowflake128::builder().with_tick(Duration::ZERO).build();
+    assert!(matches!(result, Err(SnowflakeError::InvalidTick { .. })));
+    let result = Snowflake128::builder()
+        .with_epoch_time(SystemTime::now() + Duration::from_secs(60))
+        .build();
+    assert!(matches!(result, Err(SnowflakeError::InvalidEpoch { .. })));
+    let result = Snowflake128::builder().with_epoch_time(SystemTime::UNIX_EPOCH).build();
+    assert!(matches!(result, Err(SnowflakeError::ImplausibleEpoch { .. })));
+}
+
+#[test]
+fn test_compact() {
+    use std::time::{Duration, SystemTime};
+
+    // 4 sequence bits, so the 10 IDs never wait for the next tick
+    let bits32 = CompactSnowflake::builder(CompactWidth::Bits32)
+        .with_tick(Duration::from_secs(10))
+        .with_sequence_bits(4)
+        .with_epoch_time(SystemTime::now() - Duration::from_secs(86400));
+    let bits48 = CompactSnowflake::builder(CompactWidth::Bits48);
+    for (builder, max_id) in [(bits32, 1u64 << 31), (bits48, 1 << 48)] {
+        let mut snowflake = builder.with_worker_id(1).build().unwrap();
+        let layout = snowflake.layout();
+        let ids: Vec<_> = (0..10).map(|_| snowflake.generate().unwrap()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(ids.iter().all(|&id| id < max_id));
+        assert_eq!(SnowflakeId::new(ids[0]).worker_id(&layout), 1);
+        let plan = layout.plan().unwrap();
+        assert_eq!(plan.end, snowflake.exhaustion_time());
+        assert!(snowflake.remaining_lifetime() > Duration::ZERO);
+    }
+}
+
+#[test]
+fn test_compact_exhausted() {
+    use std::time::{Duration, SystemTime};
+
+    // 28 timestamp bits of milliseconds last about 3 days
+    let mut snowflake = CompactSnowflake::builder(CompactWidth::Bits32)
+        .with_tick(Duration::from_millis(1))
+        .with_epoch_time(SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60))
+        .build()
+        .unwrap();
+    assert_eq!(snowflake.remaining_lifetime(), Duration::ZERO);
+    assert!(snowflake
+        .layout()
+        .plan()
+        .unwrap()
+        .warnings
+        .contains(&CapacityWarning::Exhausted));
+    assert_eq!(snowflake.generate(), Err(SnowflakeError::EpochExhausted));
+}
+
+#[test]
+fn test_compact_invalid_config() {
+    use std::time::{Duration, SystemTime};
+
+    let builder = || {
+        CompactSnowflake::builder(CompactWidth::Bits32)
+            .with_tick(Duration::from_secs(1))
+            .with_epoch_time(SystemTime::now() - Duration::from_secs(86400))
+    };
+    assert_eq!(
+        CompactSnowflake::builder(CompactWidth::Bits32).build().err(),
+        Some(SnowflakeError::MissingSetting { setting: "tick" })
+    );
+    assert_eq!(
+        CompactSnowflake::builder(CompactWidth::Bits32)
+            .with_tick(Duration::from_secs(1))
+            .build()
+            .err(),
+        Some(SnowflakeError::MissingSetting { setting: "epoch" })
+    );
+    assert!(CompactSnowflake::builder(CompactWidth::Bits48)
+        .build()
+        .unwrap()
+        .layout()
+        .plan()
+        .unwrap()
+        .warnings
+        .is_empty());
+    assert_eq!(
+        builder().with_worker_id_bits(30).build().err(),
+        Some(SnowflakeError::InvalidWorkerIdBits {
+            bits: 30,
+            allowed: 1..=29
+        })
+    );
+    assert_eq!(
+        builder().with_worker_id_bits(10).with_sequence_bits(0).build().err(),
+        Some(SnowflakeError::InvalidSequenceBits {
+            bits: 0,
+            allowed: 1..=20
+        })
+    );
+    assert!(matches!(
+        builder().with_worker_id(2).build(),
+        Err(SnowflakeError::InvalidWorkerId { worker_id: 2, .. })
+    ));
+    assert!(matches!(
+        builder().with_tick(Duration::ZERO).build(),
+        Err(SnowflakeError::InvalidTick { .. })
+    ));
+    assert!(matches!(
+        builder().with_epoch(1609459200000).build(),
+        Err(SnowflakeError::InvalidEpoch { .. })
+    ));
+    assert!(matches!(
+        builder().with_epoch_time(SystemTime::UNIX_EPOCH).build(),
+        Err(SnowflakeError::ImplausibleEpoch { .. })
+    ));
+}
+
+#[test]
+fn test_backfill() {
+    use std::time::{Duration, SystemTime};
+
+    let mut backfill: Backfill = Snowflake::builder().with_worker_id(0).build_backfill().unwrap();
+    let now = SystemTime::now();
+    let earlier = now - Duration::from_secs(7200);
+    let later = now - Duration::from_secs(3600);
+    let sfid1 = backfill.generate_at(later).unwrap();
+    let sfid2 = backfill.generate_at(earlier).unwrap();
+    let sfid3 = backfill.generate_at(later).unwrap();
+    assert!(sfid2 < sfid1);
+    assert!(sfid1 < sfid3);
+}
+
+#[test]
+fn test_backfill_out_of_range() {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let mut backfill = Snowflake::builder().with_worker_id(0).build_backfill().unwrap();
+    let future = SystemTime::now() + Duration::from_secs(3600);
+    assert!(matches!(
+        backfill.generate_at(future),
+        Err(SnowflakeError::TimestampInFuture { .. })
+    ));
+    let before_epoch = UNIX_EPOCH + Duration::from_secs(1609459200); // 2021-01-01 00:00:00 UTC
+    assert!(matches!(
+        backfill.generate_at(before_epoch),
+        Err(SnowflakeError::TimestampBeforeEpoch { .. })
+    ));
+}
+
 #[test]
 fn test_invalid_worker_id_bits() {
     let worker_id = 1;
@@ -48,7 +338,10 @@ fn test_invalid_worker_id_bits() {
         .with_worker_id(worker_id)
         .with_worker_id_bits(worker_id_bits)
         .build();
-    assert!(matches!(snowflake.err(), Some(SnowflakeError::ArgumentError(..))));
+    assert!(matches!(
+        snowflake.err(),
+        Some(SnowflakeError::InvalidWorkerIdBits { bits: 100, .. })
+    ));
 }
 
 #[test]
@@ -56,11 +349,1049 @@ fn test_invalid_epoch() {
     let worker_id = 1;
     let epoch = 1_000_000_000_000_000;
     let snowflake = Snowflake::builder().with_worker_id(worker_id).with_epoch(epoch).build();
-    assert!(matches!(snowflake.err(), Some(SnowflakeError::InvalidEpoch)));
+    assert!(matches!(snowflake.err(), Some(SnowflakeError::InvalidEpoch { .. })));
+}
+
+#[test]
+fn test_epoch_time() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let epoch = UNIX_EPOCH + Duration::from_secs(1609459200); // 2021-01-01 00:00:00 UTC
+    let snowflake = Snowflake::builder().with_epoch_time(epoch).build().unwrap();
+    let layout = snowflake.layout();
+    assert_eq!(layout.epoch as u128 * layout.tick.as_millis(), 1609459200000);
+}
+
+#[test]
+fn test_implausible_epoch() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let epoch = UNIX_EPOCH + Duration::from_secs(86400); // 1970-01-02 00:00:00 UTC
+    let snowflake = Snowflake::builder().with_epoch_time(epoch).build();
+    assert!(matches!(snowflake.err(), Some(SnowflakeError::ImplausibleEpoch { .. })));
+}
+
+#[test]
+fn test_without_timeout() {
+    let mut snowflake = Snowflake::builder()
+        .with_worker_id(1)
+        .without_timeout()
+        .build()
+        .unwrap();
+    assert!(snowflake.generate().is_ok());
+}
+
+#[test]
+fn test_default_layout() {
+    let snowflake = Snowflake::new(1).unwrap();
+    assert_eq!(snowflake.layout(), Layout::default());
+    let plan = snowflake.layout().plan().unwrap();
+    assert!(plan.warnings.is_empty(), "{:?}", plan.warnings);
+    assert_eq!(plan.end, Some(snowflake.exhaustion_time()));
+}
+
+#[test]
+fn test_layout_plan() {
+    use std::time::Duration;
+
+    let layout = Layout::new(Duration::from_millis(1), 41, 10, 12, 1704038400000);
+    let plan = layout.plan().unwrap();
+    assert_eq!(plan.max_workers, 1024);
+    assert_eq!(plan.max_ids_per_tick, 4096);
+    assert_eq!(plan.max_ids_per_second, 4_096_000);
+    assert_eq!(plan.lifetime, Duration::from_millis(1 << 41));
+
+    let layout = Layout::new(Duration::from_millis(1), 41, 21, 1, 1704038400000);
+    let plan = layout.plan().unwrap();
+    assert!(plan
+        .warnings
+        .contains(&CapacityWarning::NarrowSequence { max_ids_per_tick: 2 }));
+}
+
+#[test]
+fn test_compose() {
+    let worker_id = 5;
+    let mut snowflake = Snowflake::new(worker_id).unwrap();
+    let layout = snowflake.layout();
+    let sfid = snowflake.generate().unwrap();
+    let timestamp = sfid >> (layout.worker_id_bits + layout.sequence_bits);
+    let sequence = sfid & ((1 << layout.sequence_bits) - 1);
+    assert_eq!(layout.compose(timestamp, worker_id, sequence), Ok(sfid));
+}
+
+#[test]
+fn test_compose_out_of_range() {
+    let layout = Layout::default();
+    let max_timestamp = (1 << layout.timestamp_bits) - 1;
+    let max_sequence = (1 << layout.sequence_bits) - 1;
+    assert!(layout.compose(max_timestamp, 0, max_sequence).is_ok());
+    assert!(matches!(
+        layout.compose(max_timestamp + 1, 0, 0),
+        Err(SnowflakeError::InvalidTimestamp { .. })
+    ));
+    assert!(matches!(
+        layout.compose(0, 0, max_sequence + 1),
+        Err(SnowflakeError::InvalidSequence { .. })
+    ));
+}
+
+#[test]
+fn test_id_range() {
+    use std::time::{Duration, SystemTime};
+
+    let start = SystemTime::now();
+    let mut snowflake = Snowflake::new(1).unwrap();
+    let sfid = snowflake.generate().unwrap();
+    let end = SystemTime::now();
+    let layout = snowflake.layout();
+    let range = layout.id_range(start, end).unwrap();
+    assert!(range.contains(&sfid));
+
+    let before = layout.max_id_at(start - layout.tick).unwrap();
+    let after = layout.min_id_at(end + layout.tick).unwrap();
+    assert!(before < sfid && sfid < after);
+    assert!(layout.id_range(end, start - Duration::from_secs(1)).unwrap().is_empty());
+}
+
+#[test]
+fn test_decode() {
+    use std::time::{Duration, SystemTime};
+
+    let worker_id = 5;
+    let mut snowflake = Snowflake::new(worker_id).unwrap();
+    let layout = snowflake.layout();
+    let start = SystemTime::now();
+    let sfid = SnowflakeId::from(snowflake.generate().unwrap());
+    assert_eq!(sfid.worker_id(&layout), worker_id);
+    assert_eq!(
+        layout.compose(sfid.ticks(&layout), worker_id, sfid.sequence(&layout)),
+        Ok(sfid.get())
+    );
+    let timestamp = sfid.timestamp(&layout).unwrap();
+    assert!(timestamp <= SystemTime::now());
+    assert!(timestamp + layout.tick > start);
+    assert!(start.duration_since(timestamp).unwrap_or_default() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_id_parse() {
+    let sfid: SnowflakeId = "7157987372638633984".parse().unwrap();
+    assert_eq!(u64::from(sfid), 7157987372638633984);
+    assert_eq!(sfid.to_string(), "7157987372638633984");
+    assert!("snowflake".parse::<SnowflakeId>().is_err());
+    assert_eq!(
+        "18446744073709551616".parse::<SnowflakeId>(),
+        Err(SnowflakeError::IdOverflow)
+    );
+}
+
+struct Order;
+
+impl Entity for Order {
+    const PREFIX: Option<&'static str> = Some("ord");
+}
+
+struct User;
+
+impl Entity for User {
+    const PREFIX: Option<&'static str> = Some("usr");
+    const ENCODING: Encoding = Encoding::Crockford;
+}
+
+#[test]
+fn test_typed_id() {
+    let order_id = SnowflakeId::new(7157987372638633984).cast::<Order>();
+    assert_eq!(order_id.to_string(), "ord_8WlenlQjBPk");
+    assert_eq!("ord_8WlenlQjBPk".parse(), Ok(order_id));
+    let user_id = order_id.cast::<User>();
+    assert_eq!(user_id.to_string(), "usr_66NJ7JWYDQ000");
+    assert_eq!("usr_66nj7jwydq000".parse(), Ok(user_id));
+    assert_eq!(u64::from(user_id), u64::from(order_id));
+
+    assert_eq!(
+        "usr_66NJ7JWYDQ000".parse::<SnowflakeId<Order>>(),
+        Err(SnowflakeError::PrefixMismatch { expected: "ord" })
+    );
+    assert_eq!(
+        "8WlenlQjBPk".parse::<SnowflakeId<Order>>(),
+        Err(SnowflakeError::PrefixMismatch { expected: "ord" })
+    );
+    assert_eq!(
+        "ordr_8WlenlQjBPk".parse::<SnowflakeId<Order>>(),
+        Err(SnowflakeError::PrefixMismatch { expected: "ord" })
+    );
+    assert!(matches!(
+        "ord_".parse::<SnowflakeId<Order>>(),
+        Err(SnowflakeError::InvalidLength { length: 0, .. })
+    ));
+}
+
+#[test]
+fn test_coarse_clock() {
+    use std::time::{Duration, SystemTime};
+
+    let clock = CoarseClock::start(Duration::from_millis(1)).unwrap();
+    assert_eq!(clock.resolution(), Duration::from_millis(1));
+    let lag = SystemTime::now().duration_since(clock.now()).unwrap_or_default();
+    assert!(lag < Duration::from_secs(1));
+    let err = CoarseClock::start(Duration::ZERO).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let mut snowflake = Snowflake::builder()
+        .with_worker_id(1)
+        .with_clock(clock.clone())
+        .build()
+        .unwrap();
+    let layout = snowflake.layout();
+    let start = SystemTime::now();
+    // Enough IDs to use up the sequence of several ticks
+    let ids: Vec<u64> = (0..20_000).map(|_| snowflake.generate().unwrap()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    let first = SnowflakeId::new(ids[0]).timestamp(&layout).unwrap();
+    assert!(start.duration_since(first).unwrap_or_default() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_const_snowflake() {
+    let mut snowflake: ConstSnowflake<8, 12> = Snowflake::builder().with_worker_id(200).build_const().unwrap();
+    let layout = snowflake.layout();
+    assert_eq!(
+        (layout.timestamp_bits, layout.worker_id_bits, layout.sequence_bits),
+        (ConstSnowflake::<8, 12>::TIMESTAMP_BITS, 8, 12)
+    );
+
+    let ids: Vec<u64> = (0..1000).map(|_| snowflake.generate().unwrap()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    let id = SnowflakeId::new(ids[0]);
+    assert_eq!(id.worker_id(&layout), 200);
+    assert!(id.timestamp(&layout).unwrap() <= std::time::SystemTime::now());
+
+    // The builder rejects a conflicting runtime configuration
+    assert_eq!(
+        Snowflake::builder().with_worker_id(256).build_const::<8, 12>().err(),
+        Some(SnowflakeError::InvalidWorkerId {
+            worker_id: 256,
+            allowed: 0..=255
+        })
+    );
+    assert_eq!(
+        Snowflake::builder().with_worker_id_bits(4).build_const::<8, 12>().err(),
+        Some(SnowflakeError::InvalidWorkerIdBits {
+            bits: 4,
+            allowed: 8..=8
+        })
+    );
+    assert!(Snowflake::builder()
+        .with_worker_id_bits(8)
+        .build_const::<8, 12>()
+        .is_ok());
+}
+
+#[test]
+fn test_pool() {
+    use std::{sync::Barrier, thread};
+
+    let pool = Snowflake::builder().with_worker_id_bits(4).build_pool(2..=3).unwrap();
+    assert_eq!(pool.worker_ids(), 2..=3);
+    assert_eq!(pool.layout().worker_id_bits, 4);
+    let empty = std::ops::RangeInclusive::new(5, 4);
+    assert_eq!(
+        Snowflake::builder().build_pool(empty.clone()).err(),
+        Some(SnowflakeError::EmptyPool { worker_ids: empty })
+    );
+
+    // Two threads hold the two worker IDs, so a third cannot get one
+    let (leased, checked) = (Barrier::new(3), Barrier::new(3));
+    let results: Vec<_> = thread::scope(|s| {
+        let handles: Vec<_> = (0..3)
+            .map(|i| {
+                let (pool, leased, checked) = (&pool, &leased, &checked);
+                s.spawn(move || {
+                    if i == 2 {
+                        leased.wait();
+                        let id = pool.generate();
+                        checked.wait();
+                        return id;
+                    }
+                    let id = pool.generate();
+                    leased.wait();
+                    // Keep the lease until the third thread has tried its own
+                    checked.wait();
+                    id
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let layout = pool.layout();
+    let mut worker_ids: Vec<u64> = results[..2]
+        .iter()
+        .map(|id| SnowflakeId::new(*id.as_ref().unwrap()).worker_id(&layout))
+        .collect();
+    worker_ids.sort();
+    assert_eq!(worker_ids, [2, 3]);
+    assert_eq!(results[2], Err(SnowflakeError::PoolExhausted { worker_ids: 2..=3 }));
+
+    // Worker IDs are recycled once their threads exit
+    assert!(thread::scope(|s| s.spawn(|| pool.generate()).join().unwrap()).is_ok());
+}
+
+#[test]
+fn test_pool_recycled_ids_are_unique() {
+    use std::{collections::HashSet, thread};
+
+    // A single worker ID passed from thread to thread within the same periods
+    let pool = Snowflake::builder().build_pool(0..=0).unwrap();
+    let mut ids = HashSet::new();
+    for _ in 0..50 {
+        let batch: Vec<u64> = thread::scope(|s| {
+            s.spawn(|| (0..100).map(|_| pool.generate().unwrap()).collect())
+                .join()
+                .unwrap()
+        });
+        assert!(batch.into_iter().all(|id| ids.insert(id)));
+    }
+    assert_eq!(ids.len(), 5000);
+}
+
+#[test]
+fn test_next_id() {
+    let pool = Snowflake::builder().with_worker_id_bits(8).build_pool(0..=255).unwrap();
+    assert!(pool.clone().install().is_ok());
+    assert!(pool.install().is_err());
+    let (a, b) = (
+        twitter_snowflake::next_id().unwrap(),
+        twitter_snowflake::next_id().unwrap(),
+    );
+    assert!(a < b);
+}
+
+#[test]
+fn test_shards() {
+    use std::{collections::HashSet, thread};
+
+    for builder in [Snowflake::builder(), Snowflake::builder().with_relaxed_ordering()] {
+        let shards = builder.with_worker_id(5).build_shards(4).unwrap();
+        let layout = shards[0].layout();
+        let batches: Vec<(u64, Vec<u64>)> = thread::scope(|s| {
+            let handles: Vec<_> = shards
+                .into_iter()
+                .map(|mut shard| {
+                    s.spawn(move || (shard.shard(), (0..5000).map(|_| shard.generate().unwrap()).collect()))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let mut ids = HashSet::new();
+        for (shard, batch) in batches {
+            assert!(batch.windows(2).all(|w| w[0] < w[1]));
+            for id in batch {
+                let sfid = SnowflakeId::new(id);
+                assert_eq!(sfid.worker_id(&layout), 5);
+                assert_eq!(sfid.sequence(&layout) % 4, shard);
+                assert!(ids.insert(id));
+            }
+        }
+        assert_eq!(ids.len(), 20_000);
+    }
+}
+
+#[test]
+fn test_invalid_shards() {
+    let builder = Snowflake::builder().with_worker_id_bits(8);
+    let sequence_bits = builder.clone().build().unwrap().layout().sequence_bits;
+    let allowed = 1..=1 << (sequence_bits - 1);
+    for shards in [0, 3, 2 << (sequence_bits - 1)] {
+        assert_eq!(
+            builder.clone().build_shards(shards).err(),
+            Some(SnowflakeError::InvalidShards {
+                shards,
+                allowed: allowed.clone()
+            })
+        );
+    }
+    assert_eq!(builder.clone().build_shards(1).unwrap().len(), 1);
+    assert_eq!(
+        builder.build_shards(*allowed.end()).unwrap().len() as u64,
+        *allowed.end()
+    );
+}
+
+#[cfg(not(feature = "float-safe"))]
+#[test]
+fn test_sequence_policy_at_low_rate() {
+    use std::{thread, time::Duration};
+
+    let sequences = |policy| {
+        let mut snowflake = Snowflake::builder().with_sequence_policy(policy).build().unwrap();
+        let layout = snowflake.layout();
+        (0..5)
+            .map(|_| {
+                // One ID per time period
+                thread::sleep(Duration::from_millis(2));
+                SnowflakeId::new(snowflake.generate().unwrap()).sequence(&layout)
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(sequences(SequencePolicy::ResetToZero), [0; 5]);
+    let carried = sequences(SequencePolicy::Carry);
+    assert!(carried.windows(2).all(|w| w[1] == w[0] + 1));
+    assert!(sequences(SequencePolicy::RandomOffset)
+        .iter()
+        .any(|&sequence| sequence != 0));
+}
+
+#[test]
+fn test_sequence_policy_wraps() {
+    use std::collections::{HashMap, HashSet};
+
+    for policy in [
+        SequencePolicy::ResetToZero,
+        SequencePolicy::Carry,
+        SequencePolicy::RandomOffset,
+    ] {
+        // Two sequence bits, so the sequence of a time period wraps after four IDs
+        let default = Layout::default();
+        let mut snowflake = Snowflake::builder()
+            .with_worker_id_bits(default.worker_id_bits + default.sequence_bits - 2)
+            .with_sequence_policy(policy)
+            .without_timeout()
+            .build()
+            .unwrap();
+        let layout = snowflake.layout();
+        let mut ids = HashSet::new();
+        let mut per_period = HashMap::new();
+        for _ in 0..9 {
+            let id = snowflake.generate().unwrap();
+            assert!(ids.insert(id));
+            *per_period.entry(SnowflakeId::new(id).ticks(&layout)).or_insert(0) += 1;
+        }
+        assert!(per_period.values().all(|&count| count <= 4));
+    }
+}
+
+#[test]
+fn test_sequence_policy_ordering() {
+    // Two sequence bits, so the carried sequence wraps every four IDs
+    let default = Layout::default();
+    let mut snowflake = Snowflake::builder()
+        .with_worker_id_bits(default.worker_id_bits + default.sequence_bits - 2)
+        .with_sequence_policy(SequencePolicy::Carry)
+        .without_timeout()
+        .build()
+        .unwrap();
+    let layout = snowflake.layout();
+    let ids = (0..9)
+        .map(|_| SnowflakeId::new(snowflake.generate().unwrap()))
+        .collect::<Vec<_>>();
+    let mut wraps = 0;
+    for w in ids.windows(2) {
+        assert_eq!(w[1].sequence(&layout), (w[0].sequence(&layout) + 1) % 4);
+        assert!(w[1].ticks(&layout) >= w[0].ticks(&layout));
+        if w[1].ticks(&layout) == w[0].ticks(&layout) && w[1].sequence(&layout) == 0 {
+            // The sequence wrapped within a time period, so the ID went down
+            assert!(w[1] < w[0]);
+            wraps += 1;
+        }
+    }
+    assert!(wraps > 0);
+}
+
+#[test]
+fn test_i64() {
+    let mut snowflake = Snowflake::new(1).unwrap();
+    let id = snowflake.generate_i64().unwrap();
+    assert!(id > 0);
+    let sfid = SnowflakeId::try_from(id).unwrap();
+    assert_eq!(i64::try_from(sfid), Ok(id));
+    assert_eq!(SnowflakeId::try_from(-1i64), Err(SnowflakeError::NegativeId { id: -1 }));
+    assert_eq!(
+        i64::try_from(SnowflakeId::new(1 << 63)),
+        Err(SnowflakeError::NegativeId { id: i64::MIN })
+    );
+
+    // The largest ID of the default layout is still positive
+    let layout = Layout::default();
+    let (max_timestamp, max_worker_id, max_sequence) = (
+        (1 << layout.timestamp_bits) - 1,
+        (1 << layout.worker_id_bits) - 1,
+        (1 << layout.sequence_bits) - 1,
+    );
+    let max_id = layout.compose_i64(max_timestamp, max_worker_id, max_sequence).unwrap();
+    assert!(max_id > 0);
+    assert_eq!(
+        Ok(max_id as u64),
+        layout.compose(max_timestamp, max_worker_id, max_sequence)
+    );
+
+    let signed_layout = Layout {
+        timestamp_bits: 64 - layout.worker_id_bits - layout.sequence_bits,
+        ..layout
+    };
+    assert!(matches!(
+        signed_layout.compose_i64(0, 0, 0),
+        Err(SnowflakeError::InvalidTotalBits { bits: 64, .. })
+    ));
+}
+
+#[test]
+fn test_rfc3339() {
+    use std::time::Duration;
+
+    let layout = Layout::new(Duration::from_millis(1), 41, 10, 12, 1704067200000); // 2024-01-01 UTC
+    let sfid = SnowflakeId::new(layout.compose(5_097_600_123, 0, 0).unwrap()); // 59 days later
+    assert_eq!(sfid.to_rfc3339(&layout).as_deref(), Some("2024-02-29T00:00:00.123Z"));
+    let layout = Layout::new(Duration::from_secs(1), 32, 10, 11, 1704067200);
+    let sfid = SnowflakeId::new(layout.compose(31_622_399, 0, 0).unwrap());
+    assert_eq!(sfid.to_rfc3339(&layout).as_deref(), Some("2024-12-31T23:59:59Z"));
+}
+
+#[test]
+fn test_timestamp_overflow() {
+    use std::time::Duration;
+
+    // Days since an epoch far beyond what SystemTime can represent
+    let layout = Layout::new(Duration::from_secs(86400), 41, 10, 12, u64::MAX / 2);
+    let sfid = SnowflakeId::new(layout.compose(1, 0, 0).unwrap());
+    assert_eq!(sfid.timestamp(&layout), None);
+    assert_eq!(sfid.to_rfc3339(&layout), None);
+
+    let layout = Layout128 {
+        tick: Duration::from_secs(86400),
+        worker_id_bits: 10,
+        sequence_bits: 12,
+        random_bits: 8,
+        epoch: u64::MAX / 2,
+    };
+    assert_eq!(SnowflakeId128::new(1 << 30).timestamp(&layout), None);
+}
+
+#[test]
+fn test_encoding_round_trip() {
+    let encodings = [
+        Encoding::Crockford,
+        Encoding::Base58,
+        Encoding::Base62,
+        Encoding::Base64Url,
+    ];
+    let mut snowflake = Snowflake::new(1).unwrap();
+    let ids = [0, 1, 1234567890, snowflake.generate().unwrap(), u64::MAX];
+    for encoding in encodings {
+        for id in ids {
+            let sfid = SnowflakeId::new(id);
+            assert_eq!(SnowflakeId::decode(&sfid.encode(encoding), encoding), Ok(sfid));
+        }
+    }
+}
+
+#[test]
+fn test_encoding_known_values() {
+    assert_eq!(Encoding::Crockford.encode(u64::MAX), "FZZZZZZZZZZZZ");
+    assert_eq!(Encoding::Base58.encode(u64::MAX), "JPwcyDCgEup");
+    assert_eq!(Encoding::Base62.encode(u64::MAX), "LygHa16AHYF");
+    assert_eq!(Encoding::Base64Url.encode(u64::MAX), "__________8");
+    assert_eq!(Encoding::Base58.encode(1234567890), "2T6u2h");
+}
+
+#[test]
+fn test_invalid_encoding() {
+    assert_eq!(
+        Encoding::Base62.decode("1LY-VK"),
+        Err(SnowflakeError::InvalidCharacter {
+            character: '-',
+            position: 3
+        })
+    );
+    assert_eq!(
+        Encoding::Crockford.decode("14SCOPJ"),
+        Err(SnowflakeError::InvalidCharacter {
+            character: 'O',
+            position: 4
+        })
+    );
+    assert_eq!(Encoding::decode_crockford_lenient("14sc-opj"), Ok(1234567890));
+    assert!(matches!(
+        Encoding::Base58.decode(""),
+        Err(SnowflakeError::InvalidLength { length: 0, .. })
+    ));
+    assert_eq!(
+        Encoding::Crockford.decode("G000000000000"),
+        Err(SnowflakeError::IdOverflow)
+    );
+    assert!(matches!(
+        Encoding::Base64Url.decode("__________9"),
+        Err(SnowflakeError::InvalidCharacter { position: 10, .. })
+    ));
+}
+
+#[test]
+fn test_sortable_encoding() {
+    assert_eq!(Encoding::Sortable.encode(0), "0000000000000");
+    assert_eq!(Encoding::Sortable.encode(u64::MAX), "FZZZZZZZZZZZZ");
+    assert!(matches!(
+        Encoding::Sortable.decode("14SC0PJ"),
+        Err(SnowflakeError::InvalidLength { length: 7, .. })
+    ));
+    assert!(matches!(
+        Encoding::Sortable.decode("000000014SC0PJ"),
+        Err(SnowflakeError::InvalidLength { length: 14, .. })
+    ));
+    assert!(matches!(
+        Encoding::Sortable.decode("00000014sc0pj"),
+        Err(SnowflakeError::InvalidCharacter { character: 's', .. })
+    ));
+}
+
+#[test]
+fn test_obfuscation() {
+    let obfuscator = Obfuscator::new(1, 0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+    let id = 7157987372638633984;
+    let obfuscated = obfuscator.obfuscate(id);
+    assert_ne!(obfuscated, id);
+    assert!(obfuscated < 1 << 63);
+    assert_eq!(obfuscator.reveal(obfuscated), id);
+    // Consecutive IDs look unrelated
+    assert!((obfuscator.obfuscate(id + 1) ^ obfuscated).count_ones() > 8);
+    assert_ne!(Obfuscator::new(1, 42).obfuscate(id), obfuscated);
+
+    let public_id = obfuscator.encode(id, Encoding::Crockford);
+    assert!(public_id.starts_with("1."));
+    assert_eq!(obfuscator.decode(&public_id, Encoding::Crockford), Ok(id));
+    assert!(matches!(
+        obfuscator.decode("1.!", Encoding::Crockford),
+        Err(SnowflakeError::InvalidCharacter { character: '!', .. })
+    ));
+    assert_eq!(
+        obfuscator.decode("ABC", Encoding::Crockford),
+        Err(SnowflakeError::MissingKeyVersion)
+    );
+    assert_eq!(
+        obfuscator.decode("x.ABC", Encoding::Crockford),
+        Err(SnowflakeError::MissingKeyVersion)
+    );
+}
+
+#[test]
+fn test_obfuscation_key_rotation() {
+    let (old_key, new_key) = (0x1111, 0x2222);
+    let old = Obfuscator::new(1, old_key);
+    let old_public_id = old.encode(42, Encoding::Base58);
+
+    let rotated = Obfuscator::new(2, new_key).with_previous_key(1, old_key);
+    assert_eq!(rotated.version(), 2);
+    assert_eq!(rotated.decode(&old_public_id, Encoding::Base58), Ok(42));
+    let new_public_id = rotated.encode(42, Encoding::Base58);
+    assert_ne!(new_public_id, old_public_id);
+    assert_eq!(rotated.decode(&new_public_id, Encoding::Base58), Ok(42));
+    assert_eq!(
+        old.decode(&new_public_id, Encoding::Base58),
+        Err(SnowflakeError::UnknownKeyVersion { version: 2 })
+    );
+    assert!(!format!("{rotated:?}").contains("2222"));
+
+    // Only the canonical form of a version is accepted
+    let encoded = new_public_id.split_once('.').unwrap().1;
+    for version in ["+2", "02", "", "2 ", "256"] {
+        assert_eq!(
+            rotated.decode(&format!("{version}.{encoded}"), Encoding::Base58),
+            Err(SnowflakeError::MissingKeyVersion)
+        );
+    }
+    let zero = Obfuscator::new(0, new_key);
+    assert_eq!(
+        zero.decode(&zero.encode(42, Encoding::Base58), Encoding::Base58),
+        Ok(42)
+    );
+}
+
+#[test]
+fn test_checked_encoding() {
+    let sfid = SnowflakeId::new(7157987372638633984);
+    for encoding in [
+        Encoding::Crockford,
+        Encoding::Base58,
+        Encoding::Base62,
+        Encoding::Base64Url,
+        Encoding::Sortable,
+    ] {
+        let checked = sfid.encode_checked(encoding);
+        assert_eq!(checked.len(), sfid.encode(encoding).len() + 1);
+        assert!(checked.starts_with(&sfid.encode(encoding)));
+        assert_eq!(SnowflakeId::decode_checked(&checked, encoding), Ok(sfid));
+    }
+    assert_eq!(
+        Encoding::Crockford.decode_checked("66nj7jwydq0009"),
+        Ok(7157987372638633984)
+    );
+    assert_eq!(
+        Encoding::Crockford.decode_checked("66NJ7JWYDQ0008"),
+        Err(SnowflakeError::ChecksumMismatch)
+    );
+    assert!(matches!(
+        Encoding::Crockford.decode_checked("66NJ7JWYDQ00U9"),
+        Err(SnowflakeError::InvalidCharacter {
+            character: 'U',
+            position: 12
+        })
+    ));
+    assert_eq!(
+        Encoding::Sortable.decode_checked(""),
+        Err(SnowflakeError::InvalidLength {
+            length: 0,
+            allowed: 14..=14
+        })
+    );
+    assert_eq!(
+        Encoding::Sortable.decode_checked(&Encoding::Crockford.encode_checked(1)),
+        Err(SnowflakeError::InvalidLength {
+            length: 2,
+            allowed: 14..=14
+        })
+    );
+}
+
+#[test]
+fn test_uuid_ulid() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let mut snowflake = Snowflake::new(1).unwrap();
+    let layout = snowflake.layout();
+    let sfid = SnowflakeId::new(snowflake.generate().unwrap());
+    let unix_millis = sfid
+        .timestamp(&layout)
+        .unwrap()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    assert!(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() - unix_millis < 1000);
+
+    let uuid = sfid.to_uuid_v7(&layout);
+    assert_eq!(uuid >> 80, unix_millis);
+    assert_eq!((uuid >> 76) & 0xf, 7);
+    assert_eq!((uuid >> 62) & 0b11, 0b10);
+    assert_eq!(SnowflakeId::from_uuid(uuid), Ok(sfid));
+    assert_eq!((sfid.to_uuid_v8() >> 76) & 0xf, 8);
+    assert_eq!(SnowflakeId::from_uuid(sfid.to_uuid_v8()), Ok(sfid));
+
+    let ulid = sfid.to_ulid(&layout);
+    assert_eq!(ulid >> 80, unix_millis);
+    assert_eq!(SnowflakeId::from_ulid(ulid), Ok(sfid));
+
+    // A random UUIDv4 or ULID embeds no ID
+    let uuid_v4 = 0x5f2b_7c3a_9d41_4e8f_a1b2_c3d4_e5f6_0718;
+    assert_eq!(SnowflakeId::from_uuid(uuid_v4), Err(SnowflakeError::NoEmbeddedId));
+    assert_eq!(SnowflakeId::from_uuid(uuid | 1), Err(SnowflakeError::NoEmbeddedId));
+    assert_eq!(SnowflakeId::from_ulid(ulid | 1), Err(SnowflakeError::NoEmbeddedId));
+    // Without a marker, a foreign ULID with zero low bits still yields an ID
+    assert_eq!(
+        SnowflakeId::from_ulid(0xdead_beef << 16),
+        Ok(SnowflakeId::new(0xdead_beef))
+    );
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_uuid_crate() {
+    let sfid = SnowflakeId::new(0x0123_4567_89ab_cdef);
+    let uuid = uuid::Uuid::from(sfid);
+    assert_eq!(uuid.get_version_num(), 8);
+    assert_eq!(uuid.to_string(), "01234567-89ab-8cde-bc00-000000000000");
+    assert_eq!(SnowflakeId::try_from(uuid), Ok(sfid));
+    assert_eq!(
+        SnowflakeId::try_from(uuid::Uuid::nil()),
+        Err(SnowflakeError::NoEmbeddedId)
+    );
+}
+
+#[cfg(feature = "ulid")]
+#[test]
+fn test_ulid_crate() {
+    let layout = Layout::default();
+    let sfid = SnowflakeId::new(layout.compose(1000, 1, 0).unwrap());
+    let ulid = sfid.to_ulid_value(&layout);
+    assert_eq!(ulid.0, sfid.to_ulid(&layout));
+    assert_eq!(u128::from(ulid.timestamp_ms()), sfid.to_ulid(&layout) >> 80);
+    assert_eq!(SnowflakeId::try_from(ulid), Ok(sfid));
+}
+
+proptest::proptest! {
+    #[test]
+    fn test_uuid_ulid_order(a: u64, b: u64) {
+        let layout = Layout::default();
+        let mask = (1 << (layout.timestamp_bits + layout.worker_id_bits + layout.sequence_bits)) - 1;
+        let (a, b) = (a & mask, b & mask);
+        let (sfid_a, sfid_b) = (SnowflakeId::new(a), SnowflakeId::new(b));
+        proptest::prop_assert_eq!(sfid_a.to_uuid_v8().cmp(&sfid_b.to_uuid_v8()), a.cmp(&b));
+        proptest::prop_assert_eq!(sfid_a.to_uuid_v7(&layout).cmp(&sfid_b.to_uuid_v7(&layout)), a.cmp(&b));
+        proptest::prop_assert_eq!(sfid_a.to_ulid(&layout).cmp(&sfid_b.to_ulid(&layout)), a.cmp(&b));
+        proptest::prop_assert_eq!(SnowflakeId::from_uuid(sfid_a.to_uuid_v8()), Ok(sfid_a));
+        proptest::prop_assert_eq!(SnowflakeId::from_uuid(sfid_a.to_uuid_v7(&layout)), Ok(sfid_a));
+        proptest::prop_assert_eq!(SnowflakeId::from_ulid(sfid_a.to_ulid(&layout)), Ok(sfid_a));
+    }
+
+    #[test]
+    fn test_checked_encoding_detects_typos(id: u64, encoding_index in 0..5usize) {
+        let encoding = [
+            Encoding::Crockford,
+            Encoding::Base58,
+            Encoding::Base62,
+            Encoding::Base64Url,
+            Encoding::Sortable,
+        ][encoding_index];
+        let alphabet: Vec<char> = match encoding {
+            Encoding::Crockford | Encoding::Sortable => "0123456789ABCDEFGHJKMNPQRSTVWXYZ",
+            Encoding::Base58 => "123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ",
+            Encoding::Base62 => "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
+            Encoding::Base64Url => "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+        }
+        .chars()
+        .collect();
+        let checked: Vec<char> = encoding.encode_checked(id).chars().collect();
+        for position in 0..checked.len() {
+            for &character in &alphabet {
+                if character != checked[position] {
+                    let mut typo = checked.clone();
+                    typo[position] = character;
+                    let typo: String = typo.into_iter().collect();
+                    proptest::prop_assert!(encoding.decode_checked(&typo).is_err(), "{}", typo);
+                }
+            }
+        }
+        let extremes = [alphabet[0], alphabet[alphabet.len() - 1]];
+        for position in 1..checked.len() {
+            let (a, b) = (checked[position - 1], checked[position]);
+            if a != b && !(extremes.contains(&a) && extremes.contains(&b)) {
+                let mut typo = checked.clone();
+                typo.swap(position - 1, position);
+                let typo: String = typo.into_iter().collect();
+                proptest::prop_assert!(encoding.decode_checked(&typo).is_err(), "{}", typo);
+            }
+        }
+    }
+
+    #[test]
+    fn test_obfuscation_roundtrip(key: u128, id: u64) {
+        let obfuscator = Obfuscator::new(0, key);
+        let obfuscated = obfuscator.obfuscate(id);
+        proptest::prop_assert_eq!(obfuscated >> 63, id >> 63);
+        proptest::prop_assert_eq!(obfuscator.reveal(obfuscated), id);
+        let public_id = obfuscator.encode(id, Encoding::Base64Url);
+        proptest::prop_assert_eq!(obfuscator.decode(&public_id, Encoding::Base64Url), Ok(id));
+    }
+
+    #[test]
+    fn test_sortable_order(a: u64, b: u64) {
+        let (sfid_a, sfid_b) = (SnowflakeId::new(a), SnowflakeId::new(b));
+        let (text_a, text_b) = (sfid_a.encode(Encoding::Sortable), sfid_b.encode(Encoding::Sortable));
+        proptest::prop_assert_eq!(text_a.cmp(&text_b), a.cmp(&b));
+        proptest::prop_assert_eq!(sfid_a.to_sortable_bytes().cmp(&sfid_b.to_sortable_bytes()), a.cmp(&b));
+        proptest::prop_assert_eq!(SnowflakeId::decode(&text_a, Encoding::Sortable), Ok(sfid_a));
+        proptest::prop_assert_eq!(SnowflakeId::from_sortable_bytes(sfid_a.to_sortable_bytes()), sfid_a);
+    }
+
+    #[test]
+    fn test_sortable_order_adjacent(a in 0..u64::MAX) {
+        let (sfid_a, sfid_b) = (SnowflakeId::new(a), SnowflakeId::new(a + 1));
+        proptest::prop_assert!(sfid_a.encode(Encoding::Sortable) < sfid_b.encode(Encoding::Sortable));
+        proptest::prop_assert!(sfid_a.to_sortable_bytes() < sfid_b.to_sortable_bytes());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_representations() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        number: SnowflakeId,
+        #[serde(with = "twitter_snowflake::serde::as_string")]
+        string: SnowflakeId,
+        #[serde(with = "twitter_snowflake::serde::crockford")]
+        crockford: u64,
+    }
+
+    let sfid = SnowflakeId::new(7157987372638633984);
+    let row = Row {
+        number: sfid,
+        string: sfid,
+        crockford: sfid.get(),
+    };
+    let json = serde_json::to_string(&row).unwrap();
+    assert_eq!(
+        json,
+        r#"{"number":7157987372638633984,"string":"7157987372638633984","crockford":"66NJ7JWYDQ000"}"#
+    );
+    assert_eq!(serde_json::from_str::<Row>(&json).unwrap(), row);
+
+    // Both the number and the string form are accepted
+    let json = r#"{"number":"7157987372638633984","string":7157987372638633984,"crockford":7157987372638633984}"#;
+    assert_eq!(serde_json::from_str::<Row>(json).unwrap(), row);
+    assert!(serde_json::from_str::<SnowflakeId>("-1").is_err());
+    assert!(serde_json::from_str::<SnowflakeId>(r#""snowflake""#).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_binary() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        number: SnowflakeId,
+        #[serde(with = "twitter_snowflake::serde::as_string")]
+        string: SnowflakeId,
+        #[serde(with = "twitter_snowflake::serde::base62")]
+        base62: u64,
+        order: SnowflakeId<Order>,
+    }
+
+    let sfid = SnowflakeId::new(7157987372638633984);
+    let row = Row {
+        number: sfid,
+        string: sfid,
+        base62: sfid.get(),
+        order: sfid.cast(),
+    };
+    let bytes = bincode::serialize(&row).unwrap();
+    assert_eq!(bincode::deserialize::<Row>(&bytes).unwrap(), row);
+    let bytes = bincode::serialize(&sfid).unwrap();
+    assert_eq!(bytes, sfid.get().to_le_bytes());
+    assert_eq!(bincode::deserialize::<SnowflakeId>(&bytes).unwrap(), sfid);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_typed_id() {
+    let order_id = SnowflakeId::new(7157987372638633984).cast::<Order>();
+    let json = serde_json::to_string(&order_id).unwrap();
+    assert_eq!(json, r#""ord_8WlenlQjBPk""#);
+    assert_eq!(serde_json::from_str::<SnowflakeId<Order>>(&json).unwrap(), order_id);
+    assert!(serde_json::from_str::<SnowflakeId<User>>(&json).is_err());
+    assert!(serde_json::from_str::<SnowflakeId<Order>>("7157987372638633984").is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_layout() {
+    let layout = Layout::default();
+    let json = serde_json::to_string(&layout).unwrap();
+    assert_eq!(serde_json::from_str::<Layout>(&json).unwrap(), layout);
+    let invalid = Layout {
+        timestamp_bits: 64,
+        ..layout
+    };
+    let err = serde_json::from_str::<Layout>(&serde_json::to_string(&invalid).unwrap()).unwrap_err();
+    assert!(err.to_string().starts_with("invalid timestamp bits(=64)"), "{err}");
+
+    let layout = Snowflake128::builder().build().unwrap().layout();
+    let json = serde_json::to_string(&layout).unwrap();
+    assert_eq!(serde_json::from_str::<Layout128>(&json).unwrap(), layout);
+    for invalid in [
+        Layout128 {
+            worker_id_bits: 64,
+            ..layout
+        },
+        Layout128 {
+            random_bits: 64,
+            ..layout
+        },
+        Layout128 {
+            sequence_bits: layout.sequence_bits + 1,
+            ..layout
+        },
+    ] {
+        let json = serde_json::to_string(&invalid).unwrap();
+        assert!(serde_json::from_str::<Layout128>(&json).is_err());
+    }
+    let json = serde_json::to_string(&Encoding::Base58).unwrap();
+    assert_eq!(serde_json::from_str::<Encoding>(&json).unwrap(), Encoding::Base58);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono() {
+    let mut snowflake = Snowflake::new(1).unwrap();
+    let layout = snowflake.layout();
+    let sfid = SnowflakeId::from(snowflake.generate().unwrap());
+    let datetime = sfid.to_chrono(&layout).unwrap();
+    assert_eq!(std::time::SystemTime::from(datetime), sfid.timestamp(&layout).unwrap());
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time() {
+    let mut snowflake = Snowflake::new(1).unwrap();
+    let layout = snowflake.layout();
+    let sfid = SnowflakeId::from(snowflake.generate().unwrap());
+    let datetime = sfid.to_offset_date_time(&layout).unwrap();
+    assert_eq!(std::time::SystemTime::from(datetime), sfid.timestamp(&layout).unwrap());
+}
+
+#[test]
+fn test_invalid_layout() {
+    use std::time::Duration;
+
+    let layout = Layout::new(Duration::ZERO, 41, 10, 12, 1704038400000);
+    assert!(matches!(layout.plan().err(), Some(SnowflakeError::InvalidTick { .. })));
+    let layout = Layout::new(Duration::from_millis(1), 41, 0, 22, 1704038400000);
+    assert!(matches!(
+        layout.plan().err(),
+        Some(SnowflakeError::InvalidWorkerIdBits { bits: 0, .. })
+    ));
+    let layout = Layout::new(Duration::from_millis(1), 42, 10, 13, 1704038400000);
+    assert_eq!(
+        layout.plan().err(),
+        Some(SnowflakeError::InvalidTotalBits {
+            bits: 65,
+            allowed: 3..=64
+        })
+    );
+}
+
+#[cfg(not(feature = "float-safe"))]
+#[test]
+fn test_seconds_epoch() {
+    // The default mode expects epoch in milliseconds, but raw epochs are taken
+    // as given
+    let epoch = 1609459200; // 2021-01-01 00:00:00 UTC in seconds
+    assert!(Snowflake::builder().with_epoch(epoch).build().is_ok());
+    assert!(Snowflake::builder().with_epoch(0).build().is_ok());
+}
+
+#[cfg(not(feature = "float-safe"))]
+#[test]
+fn test_exhaustion_time() {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let epoch = 946684800000; // 2000-01-01 00:00:00.000 UTC
+    let snowflake = Snowflake::builder()
+        .with_worker_id(1)
+        .with_epoch(epoch)
+        .build()
+        .unwrap();
+    let exhaustion_time = snowflake.exhaustion_time();
+    assert_eq!(exhaustion_time, UNIX_EPOCH + Duration::from_millis(epoch + (1 << 41)));
+    let remaining = exhaustion_time.duration_since(SystemTime::now()).unwrap();
+    assert!(snowflake.remaining_lifetime() <= remaining);
+    assert!(snowflake.remaining_lifetime() > Duration::ZERO);
+}
+
+#[test]
+fn test_epoch_exhausted() {
+    use std::time::Duration;
+
+    // Few enough timestamp bits to run out within hours of the default epoch
+    let mut snowflake: ConstSnowflake<20, 20> = Snowflake::builder().build_const().unwrap();
+    assert!(snowflake.layout().timestamp_bits <= 23);
+    assert_eq!(snowflake.remaining_lifetime(), Duration::ZERO);
+    assert_eq!(snowflake.generate(), Err(SnowflakeError::EpochExhausted));
+    assert_eq!(snowflake.try_generate(), Err(SnowflakeError::EpochExhausted));
 }
 
 #[cfg(feature = "float-safe")]
 mod float_safe_tests {
+    use std::time::{Duration, UNIX_EPOCH};
     use twitter_snowflake::{Snowflake, SnowflakeError};
 
     /// In float-safe mode, the generated ID must fit within the exact
@@ -103,7 +1434,10 @@ mod float_safe_tests {
     fn test_invalid_worker_id() {
         // float-safe mode uses 4 worker-id bits → max worker ID = 15
         let snowflake = Snowflake::new(16);
-        assert!(matches!(snowflake.err(), Some(SnowflakeError::ArgumentError(..))));
+        assert!(matches!(
+            snowflake.err(),
+            Some(SnowflakeError::InvalidWorkerId { worker_id: 16, .. })
+        ));
     }
 
     #[test]
@@ -111,6 +1445,30 @@ mod float_safe_tests {
         // float-safe mode expects epoch in seconds — supply a far-future second value
         let epoch = 1_000_000_000_000;
         let snowflake = Snowflake::builder().with_worker_id(1).with_epoch(epoch).build();
-        assert!(matches!(snowflake.err(), Some(SnowflakeError::InvalidEpoch)));
+        assert!(matches!(snowflake.err(), Some(SnowflakeError::InvalidEpoch { .. })));
+    }
+
+    #[test]
+    fn test_raw_epoch() {
+        // float-safe mode expects epoch in seconds, and takes tiny raw values
+        // as given
+        assert!(Snowflake::builder().with_epoch(86400).build().is_ok());
+        assert!(Snowflake::builder().with_epoch(0).build().is_ok());
+    }
+
+    #[test]
+    fn test_exhaustion_time() {
+        // float-safe mode counts 32 bits of seconds since the epoch
+        let epoch = 946684800; // 2000-01-01 00:00:00 UTC
+        let snowflake = Snowflake::builder()
+            .with_worker_id(1)
+            .with_epoch(epoch)
+            .build()
+            .unwrap();
+        assert_eq!(
+            snowflake.exhaustion_time(),
+            UNIX_EPOCH + Duration::from_secs(epoch + (1 << 32))
+        );
+        assert!(snowflake.remaining_lifetime() > Duration::ZERO);
     }
 }